@@ -1,3 +1,29 @@
+use std::process::Command;
+
 fn main() {
+    // Embed the commit being built, when building from a git checkout
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .and_then(|o| String::from_utf8(o.stdout).ok());
+    if let Some(commit) = commit {
+        println!("cargo:rustc-env=PTD_GIT_COMMIT={}", commit.trim());
+    }
+    // HEAD only changes on branch switches; commits update the branch ref
+    // and the HEAD reflog. Missing paths would rerun the script every build.
+    let mut watched = vec!["../.git/HEAD".to_string(), "../.git/logs/HEAD".to_string()];
+    if let Ok(head) = std::fs::read_to_string("../.git/HEAD") {
+        if let Some(branch_ref) = head.trim().strip_prefix("ref: ") {
+            watched.push(format!("../.git/{}", branch_ref));
+        }
+    }
+    for path in watched {
+        if std::path::Path::new(&path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+
     tauri_build::build()
 }
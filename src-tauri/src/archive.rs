@@ -0,0 +1,256 @@
+//! Archive extraction and staged installs for the flash and ruffle modules.
+
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::errors;
+
+pub fn extract_zip(archive: &Path, dest: &Path) -> Result<(), String> {
+    let file =
+        fs::File::open(archive).map_err(|e| errors::io_error("Failed to open archive", &e))?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|e| format!("Failed to read zip archive: {}", e))?;
+
+    archive.extract(dest).map_err(|e| match e {
+        zip::result::ZipError::Io(e) => errors::io_error("Failed to extract archive", &e),
+        e => format!("Failed to extract archive: {}", e),
+    })?;
+    Ok(())
+}
+
+pub fn extract_tar_gz(archive: &Path, dest: &Path) -> Result<(), String> {
+    extract_tar_gz_with_progress(archive, dest, |_| {})
+}
+
+/// Rough gzip compression ratio of player archives, for estimating the
+/// uncompressed size when the gzip trailer can't be trusted
+const GZIP_RATIO_ESTIMATE: u64 = 3;
+
+/// Like [`extract_tar_gz`], calling `on_progress` with the percentage of the
+/// uncompressed data extracted so far. Progress is weighted by size, so one
+/// large binary among small license files still moves the bar smoothly.
+pub fn extract_tar_gz_with_progress<F>(
+    archive: &Path,
+    dest: &Path,
+    mut on_progress: F,
+) -> Result<(), String>
+where
+    F: FnMut(u32),
+{
+    use flate2::read::GzDecoder;
+    use tar::Archive;
+
+    let total = gzip_uncompressed_size(archive)?.max(1);
+    let file =
+        fs::File::open(archive).map_err(|e| errors::io_error("Failed to open archive", &e))?;
+
+    {
+        let mut last_percent = None;
+        let reader = ProgressReader {
+            inner: GzDecoder::new(file),
+            read: 0,
+            on_read: |read: u64| {
+                // Hold back 100 until extraction has actually finished
+                let percent = (read.saturating_mul(100) / total).min(99) as u32;
+                if last_percent != Some(percent) {
+                    last_percent = Some(percent);
+                    on_progress(percent);
+                }
+            },
+        };
+        Archive::new(reader)
+            .unpack(dest)
+            .map_err(|e| errors::io_error("Failed to extract archive", &e))?;
+    }
+
+    on_progress(100);
+    Ok(())
+}
+
+/// Uncompressed size of a gzip file from its trailer, which stores the size
+/// modulo 4 GiB. When the trailer is implausible (smaller than the compressed
+/// data, e.g. after wrapping), the size is estimated from the compressed size.
+fn gzip_uncompressed_size(path: &Path) -> Result<u64, String> {
+    let mut file =
+        fs::File::open(path).map_err(|e| errors::io_error("Failed to open archive", &e))?;
+    let compressed = file
+        .metadata()
+        .map_err(|e| format!("Failed to read archive: {}", e))?
+        .len();
+
+    let mut trailer = [0u8; 4];
+    let isize = file
+        .seek(SeekFrom::End(-4))
+        .and_then(|_| file.read_exact(&mut trailer))
+        .map(|_| u32::from_le_bytes(trailer) as u64)
+        .unwrap_or(0);
+
+    if isize >= compressed {
+        Ok(isize)
+    } else {
+        Ok(compressed.saturating_mul(GZIP_RATIO_ESTIMATE))
+    }
+}
+
+/// Reader that reports the running total of bytes read through it
+struct ProgressReader<R, F> {
+    inner: R,
+    read: u64,
+    on_read: F,
+}
+
+impl<R: Read, F: FnMut(u64)> Read for ProgressReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.read += n as u64;
+        (self.on_read)(self.read);
+        Ok(n)
+    }
+}
+
+pub fn extract_7z(archive: &Path, dest: &Path) -> Result<(), String> {
+    sevenz_rust::decompress_file(archive, dest)
+        .map_err(|e| format!("Failed to extract archive: {}", e))
+}
+
+/// Extracts the archive at the first path into the directory at the second
+pub type Extractor = fn(&Path, &Path) -> Result<(), String>;
+
+/// Pick the extractor for an archive by its file name
+pub fn extractor_for(archive_name: &str) -> Option<Extractor> {
+    if archive_name.ends_with(".zip") {
+        Some(extract_zip)
+    } else if archive_name.ends_with(".tar.gz") {
+        Some(extract_tar_gz)
+    } else if archive_name.ends_with(".7z") {
+        Some(extract_7z)
+    } else {
+        None
+    }
+}
+
+/// Executable format of a binary, from its magic number
+fn executable_format(magic: &[u8; 4]) -> Option<&'static str> {
+    match magic {
+        [0x7f, b'E', b'L', b'F'] => Some("Linux (ELF)"),
+        [0xfe, 0xed, 0xfa, 0xce | 0xcf]
+        | [0xce | 0xcf, 0xfa, 0xed, 0xfe]
+        | [0xca, 0xfe, 0xba, 0xbe] => Some("macOS (Mach-O)"),
+        [b'M', b'Z', _, _] => Some("Windows (PE)"),
+        _ => None,
+    }
+}
+
+/// Check that the executable at `path` is built for this platform, so an
+/// archive for the wrong OS fails the install instead of failing at launch
+pub fn check_binary_platform(path: &Path) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    let expected = "Linux (ELF)";
+    #[cfg(target_os = "macos")]
+    let expected = "macOS (Mach-O)";
+    #[cfg(target_os = "windows")]
+    let expected = "Windows (PE)";
+
+    let mut magic = [0u8; 4];
+    fs::File::open(path)
+        .and_then(|mut f| f.read_exact(&mut magic))
+        .map_err(|e| errors::io_error("Failed to read downloaded binary", &e))?;
+
+    match executable_format(&magic) {
+        Some(format) if format == expected => Ok(()),
+        Some(format) => Err(format!(
+            "Downloaded binary is for the wrong platform: it is a {} executable, expected {}",
+            format, expected
+        )),
+        None => Err(format!(
+            "Downloaded binary is not a recognized executable, expected {}",
+            expected
+        )),
+    }
+}
+
+/// Run `extract` into a staging directory next to `install_dir`, check that
+/// `binary` was produced, then swap the staging directory in for `install_dir`.
+///
+/// Files from a previous install are removed, except for the top-level entries
+/// named in `preserve` which are carried over into the new directory.
+pub fn stage_install<F>(
+    install_dir: &Path,
+    binary: &str,
+    preserve: &[&str],
+    extract: F,
+) -> Result<(), String>
+where
+    F: FnOnce(&Path) -> Result<(), String>,
+{
+    let staging_dir = install_dir.with_extension("staging");
+    let old_dir = install_dir.with_extension("old");
+
+    let _ = fs::remove_dir_all(&staging_dir);
+    fs::create_dir_all(&staging_dir)
+        .map_err(|e| errors::io_error("Failed to create staging directory", &e))?;
+
+    if let Err(e) = extract(&staging_dir) {
+        let _ = fs::remove_dir_all(&staging_dir);
+        return Err(e);
+    }
+
+    if !staging_dir.join(binary).exists() {
+        let _ = fs::remove_dir_all(&staging_dir);
+        return Err(format!(
+            "'{}' was not found in the downloaded archive",
+            binary
+        ));
+    }
+
+    // Swap the directories, restoring the old install if the swap fails
+    let _ = fs::remove_dir_all(&old_dir);
+    if install_dir.exists() {
+        fs::rename(install_dir, &old_dir)
+            .map_err(|e| errors::io_error("Failed to move previous install aside", &e))?;
+    }
+    if let Err(e) = fs::rename(&staging_dir, install_dir) {
+        let _ = fs::rename(&old_dir, install_dir);
+        return Err(errors::io_error(
+            "Failed to move new install into place",
+            &e,
+        ));
+    }
+
+    for name in preserve {
+        let previous = old_dir.join(name);
+        if previous.exists() {
+            let _ = fs::rename(&previous, install_dir.join(name));
+        }
+    }
+    let _ = fs::remove_dir_all(&old_dir);
+
+    Ok(())
+}
+
+/// Remove the Gatekeeper quarantine attribute from `path` and everything
+/// under it, so an extracted player opens without the "app is damaged"
+/// prompt. Files that were never quarantined are left as they are.
+#[cfg(target_os = "macos")]
+pub fn clear_quarantine(path: &Path) -> Result<(), String> {
+    let out = std::process::Command::new("xattr")
+        .arg("-dr")
+        .arg("com.apple.quarantine")
+        .arg(path)
+        .output()
+        .map_err(|e| format!("Failed to run xattr: {}", e))?;
+
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    if out.status.success() || stderr.contains("No such xattr") {
+        return Ok(());
+    }
+    Err(format!(
+        "macOS may refuse to open {} because it couldn't be taken out of quarantine ({}). \
+         Run `xattr -dr com.apple.quarantine \"{}\"` in Terminal, or allow it under \
+         System Settings > Privacy & Security.",
+        path.display(),
+        stderr.trim(),
+        path.display()
+    ))
+}
@@ -0,0 +1,125 @@
+//! Per-host circuit breaker, so a host that keeps failing is skipped for a
+//! while instead of every download waiting for it to time out.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Consecutive failures after which a host is skipped
+const FAILURE_THRESHOLD: u32 = 3;
+/// Failures further apart than this start a new count
+const FAILURE_WINDOW: Duration = Duration::from_secs(5 * 60);
+/// How long a host is skipped once its breaker opens
+const COOLDOWN: Duration = Duration::from_secs(2 * 60);
+
+#[derive(Default)]
+struct HostState {
+    consecutive_failures: u32,
+    last_failure: Option<Instant>,
+    /// Set while the host is being skipped
+    open_until: Option<Instant>,
+}
+
+/// Failure counts of download hosts, keyed by host name
+#[derive(Default)]
+pub struct HostBreakers(Mutex<HashMap<String, HostState>>);
+
+#[derive(serde::Serialize)]
+pub struct HostCircuit {
+    pub host: String,
+    pub consecutive_failures: u32,
+    /// The host is being skipped
+    pub open: bool,
+    /// Seconds until the host is tried again, while open
+    pub retry_in_secs: Option<u64>,
+}
+
+fn host_of(url: &str) -> Option<String> {
+    reqwest::Url::parse(url)
+        .ok()?
+        .host_str()
+        .map(|h| h.to_lowercase())
+}
+
+impl HostBreakers {
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, HostState>> {
+        self.0.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Refuse a request to a host whose breaker is open. Once the cooldown
+    /// has passed one attempt is let through; if it fails the breaker opens
+    /// again straight away.
+    pub fn check(&self, url: &str) -> Result<(), String> {
+        let Some(host) = host_of(url) else {
+            return Ok(());
+        };
+        let mut hosts = self.lock();
+        let Some(state) = hosts.get_mut(&host) else {
+            return Ok(());
+        };
+        match state.open_until {
+            Some(until) if until > Instant::now() => Err(format!(
+                "Skipping {}: it failed {} times in a row. Retrying in {}s",
+                host,
+                state.consecutive_failures,
+                until.saturating_duration_since(Instant::now()).as_secs()
+            )),
+            Some(_) => {
+                state.open_until = None;
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Count a failed request (no connection, or a server error)
+    pub fn record_failure(&self, url: &str) {
+        let Some(host) = host_of(url) else {
+            return;
+        };
+        let mut hosts = self.lock();
+        let state = hosts.entry(host).or_default();
+        let now = Instant::now();
+        if state
+            .last_failure
+            .is_some_and(|t| now.duration_since(t) > FAILURE_WINDOW)
+        {
+            state.consecutive_failures = 0;
+        }
+        state.consecutive_failures += 1;
+        state.last_failure = Some(now);
+        if state.consecutive_failures >= FAILURE_THRESHOLD {
+            state.open_until = Some(now + COOLDOWN);
+        }
+    }
+
+    /// Forget the failures of a host that answered
+    pub fn record_success(&self, url: &str) {
+        if let Some(host) = host_of(url) {
+            self.lock().remove(&host);
+        }
+    }
+
+    /// Hosts with recent failures, sorted by name
+    pub fn snapshot(&self) -> Vec<HostCircuit> {
+        let now = Instant::now();
+        let mut circuits: Vec<HostCircuit> = self
+            .lock()
+            .iter()
+            .map(|(host, state)| {
+                let retry_in = state
+                    .open_until
+                    .filter(|until| *until > now)
+                    .map(|until| until.duration_since(now).as_secs());
+                HostCircuit {
+                    host: host.clone(),
+                    consecutive_failures: state.consecutive_failures,
+                    open: retry_in.is_some(),
+                    retry_in_secs: retry_in,
+                }
+            })
+            .collect();
+        circuits.sort_by(|a, b| a.host.cmp(&b.host));
+        circuits
+    }
+}
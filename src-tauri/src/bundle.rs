@@ -0,0 +1,223 @@
+//! Portable zip of a game together with the Ruffle player, for playing on
+//! machines without the launcher or an internet connection.
+
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{Emitter, Window};
+
+use crate::config::{self, AppConfig, GameVersions, Settings};
+use crate::{errors, game};
+
+/// Bytes copied into the archive between `bundle-progress` events
+const PROGRESS_STEP: u64 = 1024 * 1024;
+
+/// Payload of the `bundle-progress` event
+#[derive(Clone, serde::Serialize)]
+pub struct BundleProgress {
+    pub game_id: String,
+    /// Bytes of the game and player written so far
+    pub written: u64,
+    pub total: u64,
+}
+
+/// `manifest.json` at the root of a playable bundle
+#[derive(serde::Serialize)]
+struct BundleManifest {
+    game_id: String,
+    display_name: String,
+    game_file: String,
+    game_version: Option<String>,
+    ruffle_file: String,
+    ruffle_version: String,
+    /// `{os}-{arch}` the Ruffle binary runs on
+    platform: String,
+    /// RFC 3339
+    created_at: String,
+    /// Command line to run from the extracted folder
+    launch: Vec<String>,
+}
+
+/// Quote `arg` for a POSIX shell
+fn sh_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', r"'\''"))
+}
+
+/// A script that starts Ruffle with `args` from the folder it's in
+fn launch_script(args: &[String]) -> (&'static str, String) {
+    if cfg!(target_os = "windows") {
+        let quoted: Vec<String> = args
+            .iter()
+            .map(|a| format!("\"{}\"", a.replace('%', "%%")))
+            .collect();
+        (
+            "play.bat",
+            format!("@echo off\r\ncd /d \"%~dp0\"\r\n{}\r\n", quoted.join(" ")),
+        )
+    } else {
+        let quoted: Vec<String> = args.iter().map(|a| sh_quote(a)).collect();
+        (
+            "play.sh",
+            format!(
+                "#!/bin/sh\ncd \"$(dirname \"$0\")\" || exit 1\nexec ./{}\n",
+                quoted.join(" ")
+            ),
+        )
+    }
+}
+
+/// Package a downloaded game with the current Ruffle binary, a
+/// `manifest.json` and a `play` script into a zip at `dest_path`, so it can
+/// be extracted and played on another machine of the same platform.
+/// `bundle-progress` events report the bytes written. Returns the zip path.
+#[tauri::command(async)]
+pub fn export_playable_bundle(
+    window: Window,
+    game_id: String,
+    dest_path: String,
+    config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
+) -> Result<String, String> {
+    let entry = config
+        .games
+        .get(&game_id)
+        .ok_or_else(|| format!("Game '{}' not found in configuration", game_id))?;
+    let game_path = game::find_game_path(&game_id)?
+        .ok_or_else(|| format!("Game '{}' not found. Please download it first.", game_id))?;
+
+    let settings = config::lock_settings(&settings).clone();
+    let ruffle_path = config::get_ruffle_path(&config, &settings)?;
+    if !ruffle_path.is_file() {
+        return Err("Ruffle isn't installed. Install it before exporting a bundle.".to_string());
+    }
+    let (game_version, ruffle_version) = {
+        let versions = config::lock_versions(&versions);
+        (
+            versions.games.get(&game_id).map(|v| v.version.clone()),
+            versions
+                .ruffle_active
+                .clone()
+                .unwrap_or(versions.ruffle.clone()),
+        )
+    };
+
+    let file_name = |path: &Path| {
+        path.file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    };
+    let game_file = file_name(&game_path);
+    let ruffle_file = file_name(&ruffle_path);
+
+    let mut launch = vec![ruffle_file.clone(), game_file.clone()];
+    launch.extend(game::portable_ruffle_args(&game_id, &config, &settings)?);
+    let (script_name, script) = launch_script(&launch);
+
+    let manifest = BundleManifest {
+        game_id: game_id.clone(),
+        display_name: entry.display_name.clone(),
+        game_file: game_file.clone(),
+        game_version,
+        ruffle_file: ruffle_file.clone(),
+        ruffle_version,
+        platform: format!("{}-{}", std::env::consts::OS, std::env::consts::ARCH),
+        created_at: chrono::Local::now().to_rfc3339(),
+        launch,
+    };
+    let manifest = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize bundle manifest: {}", e))?;
+
+    let size = |path: &Path| fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    let total = size(&game_path) + size(&ruffle_path);
+
+    let dest = PathBuf::from(&dest_path);
+    let partial = dest.with_extension("zip.part");
+    let written = write_bundle(
+        &partial,
+        &[
+            (game_file.as_str(), game_path.as_path(), false),
+            (ruffle_file.as_str(), ruffle_path.as_path(), true),
+        ],
+        &[
+            ("manifest.json", manifest, false),
+            (script_name, script.into_bytes(), true),
+        ],
+        |written| {
+            let _ = window.emit(
+                "bundle-progress",
+                BundleProgress {
+                    game_id: game_id.clone(),
+                    written,
+                    total,
+                },
+            );
+        },
+    )
+    .and_then(|_| {
+        fs::rename(&partial, &dest).map_err(|e| errors::io_error("Failed to save bundle", &e))
+    });
+    if let Err(e) = written {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+
+    Ok(dest.to_string_lossy().into_owned())
+}
+
+/// Write a zip of `files` (name in the zip, source path, executable) and
+/// in-memory `extras`, calling `progress` with the bytes of `files` copied
+fn write_bundle(
+    zip_path: &Path,
+    files: &[(&str, &Path, bool)],
+    extras: &[(&str, Vec<u8>, bool)],
+    progress: impl Fn(u64),
+) -> Result<(), String> {
+    let write_error = |e: &dyn std::fmt::Display| format!("Failed to write bundle: {}", e);
+    let options = |executable: bool| {
+        let options = zip::write::SimpleFileOptions::default();
+        if executable {
+            options.unix_permissions(0o755)
+        } else {
+            options.unix_permissions(0o644)
+        }
+    };
+
+    let file =
+        fs::File::create(zip_path).map_err(|e| errors::io_error("Failed to create bundle", &e))?;
+    let mut zip = zip::ZipWriter::new(file);
+
+    let mut written = 0;
+    let mut reported = 0;
+    let mut buf = vec![0u8; 64 * 1024];
+    for (name, path, executable) in files {
+        let mut source = fs::File::open(path)
+            .map_err(|e| errors::io_error(&format!("Failed to read {}", path.display()), &e))?;
+        zip.start_file(*name, options(*executable))
+            .map_err(|e| write_error(&e))?;
+        loop {
+            let n = source
+                .read(&mut buf)
+                .map_err(|e| errors::io_error(&format!("Failed to read {}", path.display()), &e))?;
+            if n == 0 {
+                break;
+            }
+            zip.write_all(&buf[..n]).map_err(|e| write_error(&e))?;
+            written += n as u64;
+            if written - reported >= PROGRESS_STEP {
+                reported = written;
+                progress(written);
+            }
+        }
+    }
+    progress(written);
+
+    for (name, contents, executable) in extras {
+        zip.start_file(*name, options(*executable))
+            .map_err(|e| write_error(&e))?;
+        zip.write_all(contents).map_err(|e| write_error(&e))?;
+    }
+    zip.finish().map_err(|e| write_error(&e))?;
+    Ok(())
+}
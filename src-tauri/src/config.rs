@@ -4,7 +4,10 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::errors;
 
 /// Flash player configuration per OS
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -82,12 +85,60 @@ impl Default for RuffleConfig {
     }
 }
 
+/// A game in the catalog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameEntry {
+    pub url: String,
+    pub display_name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    /// SHA-256 of a copy of the game bundled in the app resources as
+    /// `games/{id}.swf`, installed when the game can't be downloaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bundled_sha256: Option<String>,
+    /// Position in the game list; lower comes first. Games without one follow,
+    /// ordered by the PTD number in their id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<i64>,
+    /// Extra Ruffle flags for builds whose preloader or loader SWF Ruffle
+    /// handles differently, e.g. `["--no-auto-play"]`. Appended after the
+    /// launcher's own flags.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ruffle_load_behavior: Vec<String>,
+}
+
 /// Main application configuration (loaded from config.json)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
     pub flash_player: FlashPlayerConfig,
     pub ruffle: RuffleConfig,
+    /// Game catalog by id
+    #[serde(default)]
+    pub games: HashMap<String, GameEntry>,
+    /// Deprecated id → URL map from older configs. Moved into `games` by
+    /// `load_config`, so it is always empty at runtime.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub game_urls: HashMap<String, String>,
+    /// Extra request headers per game id (e.g. tokens for private mirrors)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub game_headers: HashMap<String, HashMap<String, String>>,
+    /// File used by the download speed test (defaults to the PTD1 SWF)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed_test_url: Option<String>,
+    /// Expected download sizes in bytes by item ("flash_player", "ruffle" or a
+    /// game id), used for progress when the server sends no Content-Length
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub expected_sizes: HashMap<String, u64>,
+    /// Ruffle command-line flags to rename for the installed Ruffle, e.g.
+    /// `"--spoof-url": "--url"`. An empty replacement drops the flag and its
+    /// value.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub ruffle_flag_compat: HashMap<String, String>,
+    /// File this config was loaded from; `None` for the built-in default
+    #[serde(skip)]
+    pub source_path: Option<PathBuf>,
 }
 
 impl Default for AppConfig {
@@ -95,35 +146,235 @@ impl Default for AppConfig {
         Self {
             flash_player: FlashPlayerConfig::default(),
             ruffle: RuffleConfig::default(),
-            game_urls: [
-                (
-                    "PTD1".to_string(),
-                    "https://ptd.onl/ptd1-latest.swf".to_string(),
-                ),
+            games: [
+                ("PTD1", "PTD 1", "Pokemon Tower Defense", "ptd1-latest.swf"),
                 (
-                    "PTD1_Hacked".to_string(),
-                    "https://ptd.onl/ptd1-hacked-latest.swf".to_string(),
+                    "PTD1_Hacked",
+                    "PTD 1 Hacked",
+                    "Pokemon Tower Defense (Hacked)",
+                    "ptd1-hacked-latest.swf",
                 ),
                 (
-                    "PTD2".to_string(),
-                    "https://ptd.onl/ptd2-latest.swf".to_string(),
+                    "PTD2",
+                    "PTD 2",
+                    "Pokemon Tower Defense 2",
+                    "ptd2-latest.swf",
                 ),
                 (
-                    "PTD2_Hacked".to_string(),
-                    "https://ptd.onl/ptd2-hacked-latest.swf".to_string(),
+                    "PTD2_Hacked",
+                    "PTD 2 Hacked",
+                    "Pokemon Tower Defense 2 (Hacked)",
+                    "ptd2-hacked-latest.swf",
                 ),
                 (
-                    "PTD3".to_string(),
-                    "https://ptd.onl/ptd3-latest.swf".to_string(),
+                    "PTD3",
+                    "PTD 3",
+                    "Pokemon Tower Defense 3",
+                    "ptd3-latest.swf",
                 ),
                 (
-                    "PTD3_Hacked".to_string(),
-                    "https://ptd.onl/ptd3-hacked-latest.swf".to_string(),
+                    "PTD3_Hacked",
+                    "PTD 3 Hacked",
+                    "Pokemon Tower Defense 3 (Hacked)",
+                    "ptd3-hacked-latest.swf",
                 ),
             ]
             .into_iter()
+            .map(|(id, display_name, description, file)| {
+                (
+                    id.to_string(),
+                    GameEntry {
+                        url: format!("https://ptd.onl/{}", file),
+                        display_name: display_name.to_string(),
+                        description: description.to_string(),
+                        icon_url: None,
+                        bundled_sha256: None,
+                        order: None,
+                        ruffle_load_behavior: Vec::new(),
+                    },
+                )
+            })
             .collect(),
+            game_urls: HashMap::new(),
+            game_headers: HashMap::new(),
+            speed_test_url: None,
+            expected_sizes: HashMap::new(),
+            ruffle_flag_compat: HashMap::new(),
+            source_path: None,
+        }
+    }
+}
+
+impl AppConfig {
+    /// Move entries from the deprecated `game_urls` map into `games`, using the
+    /// id as display name. Entries already in `games` take precedence.
+    pub fn migrate_game_urls(mut self) -> Self {
+        for (id, url) in std::mem::take(&mut self.game_urls) {
+            self.games.entry(id.clone()).or_insert(GameEntry {
+                url,
+                display_name: id,
+                description: String::new(),
+                icon_url: None,
+                bundled_sha256: None,
+                order: None,
+                ruffle_load_behavior: Vec::new(),
+            });
+        }
+        self
+    }
+
+    /// Union the bundled game list into this config.
+    /// Ids this config already defines keep their entries; ids it is missing are
+    /// added from `bundled` so newly shipped games always appear.
+    pub fn merge_bundled(mut self, bundled: &AppConfig) -> Self {
+        for (id, entry) in &bundled.games {
+            self.games
+                .entry(id.clone())
+                .or_insert_with(|| entry.clone());
+        }
+        self
+    }
+
+    /// Download URL of a game
+    pub fn game_url(&self, game_id: &str) -> Option<&str> {
+        self.games.get(game_id).map(|g| g.url.as_str())
+    }
+
+    /// Download URL of a game, pointed at the configured backend if any
+    pub fn resolve_game_url(&self, game_id: &str, settings: &Settings) -> Option<String> {
+        let url = self.game_url(game_id)?;
+        let Some(base) = settings.backend_base.as_deref() else {
+            return Some(url.to_string());
+        };
+        match rewrite_url_host(url, base) {
+            Ok(rewritten) => Some(rewritten),
+            Err(e) => {
+                crate::logger::log(&format!(
+                    "Warning: not applying backend to {}: {}",
+                    game_id, e
+                ));
+                Some(url.to_string())
+            }
+        }
+    }
+
+    /// Game ids in display order: by `order` where set, then by the PTD
+    /// number in the id with the normal game before its hacked variant, then
+    /// by id
+    pub fn display_order(&self) -> Vec<&String> {
+        let mut ids = self.game_ids();
+        ids.sort_by_cached_key(|id| {
+            let order = self.games[*id].order.unwrap_or(i64::MAX);
+            let number: Option<u32> = id
+                .chars()
+                .skip_while(|c| !c.is_ascii_digit())
+                .take_while(|c| c.is_ascii_digit())
+                .collect::<String>()
+                .parse()
+                .ok();
+            let hacked = id.to_lowercase().contains("hack");
+            (order, number.unwrap_or(u32::MAX), hacked, (*id).clone())
+        });
+        ids
+    }
+
+    /// All game ids, sorted
+    pub fn game_ids(&self) -> Vec<&String> {
+        let mut ids: Vec<&String> = self.games.keys().collect();
+        ids.sort();
+        ids
+    }
+}
+
+/// Replace the scheme, host and port of `url` with those of `base`. A path in
+/// `base` is kept as a prefix, so `https://mirror.example/ptd` turns
+/// `https://ptd.onl/games/ptd1.swf` into `https://mirror.example/ptd/games/ptd1.swf`.
+pub fn rewrite_url_host(url: &str, base: &str) -> Result<String, String> {
+    let base = parse_backend_base(base)?;
+    let mut parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+
+    parsed
+        .set_scheme(base.scheme())
+        .map_err(|_| "Failed to set URL scheme".to_string())?;
+    parsed
+        .set_host(base.host_str())
+        .map_err(|e| format!("Failed to set URL host: {}", e))?;
+    parsed
+        .set_port(base.port())
+        .map_err(|_| "Failed to set URL port".to_string())?;
+
+    let prefix = base.path().trim_end_matches('/');
+    if !prefix.is_empty() {
+        let path = format!("{}{}", prefix, parsed.path());
+        parsed.set_path(&path);
+    }
+    Ok(parsed.to_string())
+}
+
+/// Longest URL accepted from the config or settings
+pub const MAX_URL_LENGTH: usize = 2048;
+
+/// Check a URL coming from the config or settings before the launcher
+/// fetches anything from it: it must be http or https with a host, at most
+/// [`MAX_URL_LENGTH`] long and free of control characters. `file:` and other
+/// local schemes are rejected so a config can't make the launcher read local
+/// files. Errors start with `field`.
+pub fn validate_url(field: &str, url: &str) -> Result<reqwest::Url, String> {
+    if url.len() > MAX_URL_LENGTH {
+        return Err(format!(
+            "{}: URL is longer than {} characters",
+            field, MAX_URL_LENGTH
+        ));
+    }
+    // The URL parser silently drops tabs and newlines, so check before parsing
+    if url.chars().any(char::is_control) {
+        return Err(format!("{}: URL contains control characters", field));
+    }
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|e| format!("{}: invalid URL '{}': {}", field, redact_url(url), e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!(
+            "{}: '{}' uses the {} scheme. Only http and https URLs are allowed",
+            field,
+            redact_url(url),
+            parsed.scheme()
+        ));
+    }
+    if !parsed.has_host() {
+        return Err(format!("{}: '{}' has no host", field, redact_url(url)));
+    }
+    Ok(parsed)
+}
+
+/// Parse a backend base URL, which must pass [`validate_url`]
+pub fn parse_backend_base(base: &str) -> Result<reqwest::Url, String> {
+    validate_url("backend_base", base)
+}
+
+/// Longest game id accepted
+pub const MAX_GAME_ID_LENGTH: usize = 64;
+
+/// Whether `id` can be a game id. Ids become file names in the games
+/// directory, so only letters, digits, '_' and '-' are allowed.
+pub fn is_valid_game_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_GAME_ID_LENGTH
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Strip any embedded credentials from a URL so it is safe to display or log
+pub fn redact_url(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(mut parsed) => {
+            if !parsed.username().is_empty() || parsed.password().is_some() {
+                let _ = parsed.set_username("***");
+                let _ = parsed.set_password(None);
+            }
+            parsed.to_string()
         }
+        Err(_) => "<invalid url>".to_string(),
     }
 }
 
@@ -135,7 +386,113 @@ pub struct GameVersions {
     #[serde(default)]
     pub ruffle: String,
     #[serde(default)]
-    pub games: HashMap<String, String>,
+    pub games: HashMap<String, GameVersion>,
+    /// Number of launches per game id
+    #[serde(default)]
+    pub play_counts: HashMap<String, u64>,
+    /// Hashes of installed files by absolute path, recorded at install time
+    #[serde(default)]
+    pub file_hashes: HashMap<String, FileHash>,
+    /// URL each game was last downloaded from, by game id
+    #[serde(default)]
+    pub game_sources: HashMap<String, String>,
+    /// Number of times Ruffle crashed shortly after launching, per game id
+    #[serde(default)]
+    pub crash_counts: HashMap<String, u64>,
+    /// Bytes downloaded per item ("flash_player", "ruffle" or a game id)
+    /// since `data_usage_since`
+    #[serde(default)]
+    pub data_usage: HashMap<String, u64>,
+    /// When data usage counting started (seconds since the Unix epoch)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data_usage_since: Option<i64>,
+    /// Tag of the cached Ruffle build picked with `set_active_ruffle`; `None`
+    /// while the managed install is used
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ruffle_active: Option<String>,
+}
+
+/// Installed build of a game
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "StoredGameVersion")]
+pub struct GameVersion {
+    /// Download time in seconds since the Unix epoch (also the suffix of
+    /// versioned `{id}-v*.swf` files), or "bundled"
+    pub version: String,
+    /// When the build was installed, as an RFC 3339 timestamp
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installed_at: Option<String>,
+    /// `Last-Modified` the server sent with the download
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+    /// `ETag` the server sent with the download
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+}
+
+impl GameVersion {
+    /// A version with nothing known beyond its id. A numeric id is a download
+    /// timestamp, so `installed_at` is derived from it.
+    pub fn new(version: String) -> Self {
+        let installed_at = version
+            .parse::<i64>()
+            .ok()
+            .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+            .map(|t| t.to_rfc3339());
+        Self {
+            version,
+            installed_at,
+            last_modified: None,
+            etag: None,
+        }
+    }
+}
+
+/// A game version as stored in version.json
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredGameVersion {
+    /// Older launchers stored only the version string
+    Legacy(String),
+    Full {
+        version: String,
+        #[serde(default)]
+        installed_at: Option<String>,
+        #[serde(default)]
+        last_modified: Option<String>,
+        #[serde(default)]
+        etag: Option<String>,
+    },
+}
+
+impl From<StoredGameVersion> for GameVersion {
+    fn from(stored: StoredGameVersion) -> Self {
+        match stored {
+            StoredGameVersion::Legacy(version) => GameVersion::new(version),
+            StoredGameVersion::Full {
+                version,
+                installed_at,
+                last_modified,
+                etag,
+            } => GameVersion {
+                installed_at: installed_at
+                    .or_else(|| GameVersion::new(version.clone()).installed_at),
+                version,
+                last_modified,
+                etag,
+            },
+        }
+    }
+}
+
+/// Hash of an installed file, with the size and modification time it had
+/// when hashed so unchanged files can skip re-hashing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileHash {
+    pub sha256: String,
+    pub size: u64,
+    /// Milliseconds since the Unix epoch
+    pub modified_ms: u64,
 }
 
 /// User settings (stored in settings.json)
@@ -149,6 +506,461 @@ pub struct Settings {
     pub ruffle_path: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sound_enabled: Option<bool>,
+    /// Ruffle stage scale mode: "exact", "show_all", "no_border" or "no_scale"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ruffle_scale: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ruffle_width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ruffle_height: Option<u32>,
+    /// Keep previous game builds as `{id}-v{timestamp}.swf` instead of overwriting
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_old_versions: Option<bool>,
+    /// On macOS, symlink Flash Player.app from a kept-mounted DMG instead of copying it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub macos_flash_symlink: Option<bool>,
+    /// Send no-cache headers and a cache-busting query parameter with game downloads
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bypass_cache: Option<bool>,
+    /// Id of the game launched most recently, so the UI can preselect it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_played: Option<String>,
+    /// Advanced: hex SHA-256 hashes of allowed server certificate public keys
+    /// (SPKI). When set, connections to servers whose leaf certificate key isn't
+    /// listed are rejected, so downloads break when a CDN rotates its keys.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pinned_cert_sha256: Option<Vec<String>>,
+    /// Where Ruffle stores save data; relative paths are resolved against the
+    /// app directory. Ruffle's own default location is used when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ruffle_storage_dir: Option<String>,
+    /// Base URL of a PTD mirror, e.g. `https://mirror.example`. When set, game
+    /// URLs are rewritten to this scheme and host at resolution time.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backend_base: Option<String>,
+    /// Linux only: run the Windows Flash projector through Wine instead of the
+    /// native Linux projector
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub use_wine_flash: Option<bool>,
+    /// Debug: log the status and key response headers of each download
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_response_headers: Option<bool>,
+    /// Extra environment variables for the player process, e.g.
+    /// `RUST_LOG=ruffle=debug`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub launch_env: Option<HashMap<String, String>>,
+    /// Allow `launch_env` to override variables the player needs to start,
+    /// such as `PATH`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub launch_env_override_critical: Option<bool>,
+    /// Largest download allowed, in bytes; 500 MB when unset. 0 removes the
+    /// limit, which also removes the safety net against runaway or
+    /// misconfigured downloads filling the disk.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_download_bytes: Option<u64>,
+    /// GitHub repo (`owner/name`) Ruffle nightlies are downloaded from, for
+    /// forks that publish their own builds; `ruffle-rs/ruffle` when unset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ruffle_repo: Option<String>,
+    /// Program run after every successful download with the item name and
+    /// installed path as arguments. It runs with the launcher's permissions,
+    /// so only point it at a trusted executable; it is run directly, not
+    /// through a shell. Unset by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_download_command: Option<String>,
+    /// Check for a newer Ruffle on startup and download it in the background.
+    /// Off by default, since an update is a large download on metered
+    /// connections.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_update_ruffle: Option<bool>,
+    /// Mirrors of the GitHub releases API, tried in order when
+    /// api.github.com can't be reached. `{repo}` is replaced with the Ruffle
+    /// repo, e.g. `https://ghproxy.example/api/repos/{repo}/releases`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ruffle_metadata_mirrors: Option<Vec<String>>,
+    /// Seconds to wait for each Ruffle release list request before giving up
+    /// on that source. Defaults to 15.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_fetch_timeout_secs: Option<u64>,
+    /// Keep downloaded Ruffle archives in the Ruffle cache directory instead
+    /// of deleting them after install. See `prune_ruffle_cache`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_ruffle_archives: Option<bool>,
+    /// Download each game into its own `{id}` subfolder of the games
+    /// directory instead of the games directory itself. Games already
+    /// downloaded in the other layout are still found.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub games_in_subfolders: Option<bool>,
+    /// `RUST_LOG` filter Ruffle is started with, e.g. `debug` or
+    /// `ruffle_core=trace`. When set, Ruffle's output goes to `ruffle.log`
+    /// instead of the launcher log.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ruffle_log_level: Option<String>,
+    /// Flash projector to use for specific games instead of the global one,
+    /// by game id, for titles that only run on a particular Flash version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub game_flash_paths: Option<HashMap<String, String>>,
+    /// Launch once more when the player fails within a couple of seconds of
+    /// starting, for setups where the first start after install is flaky
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_retry_launch: Option<bool>,
+}
+
+/// Repo Ruffle nightlies come from when `ruffle_repo` isn't set
+pub const DEFAULT_RUFFLE_REPO: &str = "ruffle-rs/ruffle";
+
+/// URL of a `ruffle_metadata_mirrors` entry for `repo`
+pub fn mirror_url(mirror: &str, repo: &str) -> String {
+    mirror.replace("{repo}", repo)
+}
+
+/// Whether `repo` looks like a GitHub `owner/name`
+pub fn is_valid_github_repo(repo: &str) -> bool {
+    let valid_part = |part: &str| {
+        !part.is_empty()
+            && part != "."
+            && part != ".."
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    matches!(repo.split_once('/'), Some((owner, name)) if valid_part(owner) && valid_part(name))
+}
+
+/// Scale modes accepted in settings, mapped to Ruffle's `--scale` values
+pub const RUFFLE_SCALE_MODES: [(&str, &str); 4] = [
+    ("exact", "exact-fit"),
+    ("show_all", "show-all"),
+    ("no_border", "no-border"),
+    ("no_scale", "no-scale"),
+];
+
+/// Map a settings scale mode to the value Ruffle expects for `--scale`
+pub fn ruffle_scale_arg(scale: &str) -> Option<&'static str> {
+    RUFFLE_SCALE_MODES
+        .iter()
+        .find(|(name, _)| *name == scale)
+        .map(|(_, arg)| *arg)
+}
+
+/// Largest Ruffle window dimension accepted, in pixels
+const MAX_RUFFLE_WINDOW_SIZE: u32 = 16384;
+
+/// Longest accepted `release_fetch_timeout_secs`
+const MAX_RELEASE_FETCH_TIMEOUT_SECS: u64 = 300;
+
+/// A settings field that failed [`Settings::validate`]
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationError {
+    /// Name of the field in settings.json
+    pub field: String,
+    pub message: String,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.field, self.message)
+    }
+}
+
+impl Settings {
+    /// Problems that don't stop the settings from being saved: a per-game
+    /// Flash Player that is missing on disk only matters when that game is
+    /// launched, which reports it again
+    pub fn warnings(&self) -> Vec<ValidationError> {
+        self.game_flash_paths
+            .iter()
+            .flatten()
+            .filter(|(game_id, path)| is_valid_game_id(game_id) && !Path::new(path).exists())
+            .map(|(game_id, path)| ValidationError {
+                field: "game_flash_paths".to_string(),
+                message: format!("Flash Player for '{}' not found at {}", game_id, path),
+            })
+            .collect()
+    }
+
+    /// Check every field and the constraints between fields, returning all
+    /// problems found rather than stopping at the first
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        let mut error = |field: &str, message: String| {
+            errors.push(ValidationError {
+                field: field.to_string(),
+                message,
+            })
+        };
+
+        if let Some(scale) = &self.ruffle_scale {
+            if ruffle_scale_arg(scale).is_none() {
+                let accepted: Vec<&str> =
+                    RUFFLE_SCALE_MODES.iter().map(|(name, _)| *name).collect();
+                error(
+                    "ruffle_scale",
+                    format!(
+                        "Invalid Ruffle scale '{}'. Expected one of: {}",
+                        scale,
+                        accepted.join(", ")
+                    ),
+                );
+            }
+        }
+
+        for (field, size) in [
+            ("ruffle_width", self.ruffle_width),
+            ("ruffle_height", self.ruffle_height),
+        ] {
+            match size {
+                Some(0) => error(
+                    field,
+                    "Ruffle window size must be greater than zero".to_string(),
+                ),
+                Some(size) if size > MAX_RUFFLE_WINDOW_SIZE => error(
+                    field,
+                    format!(
+                        "Ruffle window size must be at most {} pixels",
+                        MAX_RUFFLE_WINDOW_SIZE
+                    ),
+                ),
+                _ => {}
+            }
+        }
+
+        for pin in self.pinned_cert_sha256.iter().flatten() {
+            if !crate::tls::is_valid_pin(pin) {
+                error(
+                    "pinned_cert_sha256",
+                    format!(
+                        "Invalid certificate pin '{}'. Expected a hex SHA-256 hash",
+                        pin
+                    ),
+                );
+            }
+        }
+
+        if let Some(base) = &self.backend_base {
+            if let Err(e) = parse_backend_base(base) {
+                error("backend_base", e);
+            }
+        }
+
+        if let Some(repo) = &self.ruffle_repo {
+            if !is_valid_github_repo(repo) {
+                error(
+                    "ruffle_repo",
+                    format!("Invalid Ruffle repo '{}'. Expected 'owner/name'", repo),
+                );
+            }
+        }
+
+        for game_id in self.game_flash_paths.iter().flatten().map(|(id, _)| id) {
+            if !is_valid_game_id(game_id) {
+                error("game_flash_paths", format!("Invalid game id '{}'", game_id));
+            }
+        }
+
+        if let Some(level) = &self.ruffle_log_level {
+            if level.trim().is_empty() || level.chars().any(char::is_whitespace) {
+                error(
+                    "ruffle_log_level",
+                    format!(
+                        "Invalid Ruffle log level '{}'. Expected a RUST_LOG filter such as 'debug'",
+                        level
+                    ),
+                );
+            }
+        }
+
+        if let Some(timeout) = self.release_fetch_timeout_secs {
+            if !(1..=MAX_RELEASE_FETCH_TIMEOUT_SECS).contains(&timeout) {
+                error(
+                    "release_fetch_timeout_secs",
+                    format!(
+                        "Release fetch timeout must be between 1 and {} seconds",
+                        MAX_RELEASE_FETCH_TIMEOUT_SECS
+                    ),
+                );
+            }
+        }
+
+        for mirror in self.ruffle_metadata_mirrors.iter().flatten() {
+            if let Err(e) = validate_url(
+                "ruffle_metadata_mirrors",
+                &mirror_url(mirror, DEFAULT_RUFFLE_REPO),
+            ) {
+                error("ruffle_metadata_mirrors", e);
+            }
+        }
+
+        let override_critical = self.launch_env_override_critical.unwrap_or(false);
+        for key in self.launch_env.iter().flat_map(|env| env.keys()) {
+            if key.is_empty() || key.contains(['=', '\0']) {
+                error(
+                    "launch_env",
+                    format!("Invalid environment variable name '{}'", key),
+                );
+                continue;
+            }
+            let critical = CRITICAL_ENV_VARS
+                .iter()
+                .any(|var| var.eq_ignore_ascii_case(key));
+            if critical && !override_critical {
+                error(
+                    "launch_env",
+                    format!(
+                        "Setting {} for the player can stop it from starting. Enable \
+                         launch_env_override_critical to override it anyway.",
+                        key
+                    ),
+                );
+            }
+        }
+
+        errors
+    }
+}
+
+/// Validate user settings before they are saved, failing with every problem
+/// found by [`Settings::validate`]. [`Settings::warnings`] are only logged.
+pub fn validate_settings(settings: &Settings) -> Result<(), String> {
+    for warning in settings.warnings() {
+        crate::logger::log(&format!("Warning: {}", warning));
+    }
+    let errors = settings.validate();
+    if errors.is_empty() {
+        return Ok(());
+    }
+    Err(errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Environment variables `launch_env` may only override when
+/// `launch_env_override_critical` is set
+const CRITICAL_ENV_VARS: [&str; 8] = [
+    "PATH",
+    "HOME",
+    "USERPROFILE",
+    "SYSTEMROOT",
+    "TEMP",
+    "TMP",
+    "LD_LIBRARY_PATH",
+    "DYLD_LIBRARY_PATH",
+];
+
+/// Check the URLs and file names in a config, returning every problem found
+pub fn validate_config(config: &AppConfig) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    let mut check_url = |what: &str, url: &str| {
+        if let Err(e) = validate_url(what, url) {
+            errors.push(e);
+        }
+    };
+
+    let flash = [
+        ("windows", &config.flash_player.windows),
+        ("macos", &config.flash_player.macos),
+        ("linux", &config.flash_player.linux),
+    ];
+    for (os, entry) in flash {
+        check_url(
+            &format!("flash_player.{}.primary_url", os),
+            &entry.primary_url,
+        );
+        if let Some(fallback) = &entry.fallback_url {
+            check_url(&format!("flash_player.{}.fallback_url", os), fallback);
+        }
+    }
+    let ruffle = [
+        ("windows", &config.ruffle.windows),
+        ("macos", &config.ruffle.macos),
+        ("linux", &config.ruffle.linux),
+    ];
+    for (os, entry) in ruffle {
+        check_url(&format!("ruffle.{}.url", os), &entry.url);
+    }
+
+    let mut ids: Vec<&String> = config.games.keys().collect();
+    ids.sort();
+    for id in &ids {
+        check_url(&format!("games.{}.url", id), &config.games[*id].url);
+        if let Some(icon) = &config.games[*id].icon_url {
+            check_url(&format!("games.{}.icon_url", id), icon);
+        }
+    }
+    if let Some(url) = &config.speed_test_url {
+        check_url("speed_test_url", url);
+    }
+
+    for id in &ids {
+        if let Some(hash) = &config.games[*id].bundled_sha256 {
+            if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                errors.push(format!(
+                    "games.{}.bundled_sha256: '{}' is not a hex SHA-256 hash",
+                    id, hash
+                ));
+            }
+        }
+    }
+
+    // Player file names are joined onto install directories
+    let filenames = [
+        (
+            "flash_player.windows.filename",
+            &config.flash_player.windows.filename,
+        ),
+        (
+            "flash_player.macos.filename",
+            &config.flash_player.macos.filename,
+        ),
+        (
+            "flash_player.linux.filename",
+            &config.flash_player.linux.filename,
+        ),
+        ("ruffle.windows.filename", &config.ruffle.windows.filename),
+        ("ruffle.macos.filename", &config.ruffle.macos.filename),
+        ("ruffle.linux.filename", &config.ruffle.linux.filename),
+    ];
+    for (what, filename) in filenames {
+        if filename.is_empty()
+            || filename.contains(['/', '\\'])
+            || filename == "."
+            || filename == ".."
+        {
+            errors.push(format!("{}: invalid file name '{}'", what, filename));
+        }
+    }
+
+    // Game ids become file names in the games directory
+    for id in &ids {
+        if !is_valid_game_id(id) {
+            errors.push(format!(
+                "games: invalid game id '{}'. Use up to {} letters, digits, '_' and '-'",
+                id, MAX_GAME_ID_LENGTH
+            ));
+        }
+    }
+    if ids.is_empty() {
+        errors.push("games: no games defined".to_string());
+    }
+
+    for id in config.game_headers.keys() {
+        if !config.games.contains_key(id) {
+            errors.push(format!("game_headers: unknown game id '{}'", id));
+        }
+    }
+
+    errors
+}
+
+/// Lock the managed settings, recovering the value if the mutex was poisoned
+/// so a panic elsewhere can't break every later command
+pub fn lock_settings(settings: &Mutex<Settings>) -> MutexGuard<'_, Settings> {
+    settings
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
 }
 
 /// Get the application data directory based on OS
@@ -169,12 +981,50 @@ pub fn get_app_dir() -> Result<PathBuf, String> {
 
     #[cfg(target_os = "linux")]
     {
+        // Flatpak points XDG_DATA_HOME at the app's own writable data
+        // directory; ~/.local/share is outside the sandbox
+        if detect_sandbox() == Sandbox::Flatpak {
+            if let Some(data_home) = std::env::var_os("XDG_DATA_HOME") {
+                return Ok(PathBuf::from(data_home).join("PTD Launcher"));
+            }
+        }
         dirs::home_dir()
             .map(|p| p.join(".local/share/PTD Launcher"))
             .ok_or_else(|| "Failed to get home directory".to_string())
     }
 }
 
+/// Linux packaging format the launcher is running from
+// Only Linux detects anything other than `None`
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Sandbox {
+    None,
+    Flatpak,
+    Snap,
+    AppImage,
+}
+
+/// Detect whether the launcher runs inside a Flatpak or Snap sandbox or from
+/// an AppImage mount. Always `Sandbox::None` on other platforms.
+pub fn detect_sandbox() -> Sandbox {
+    #[cfg(target_os = "linux")]
+    {
+        let has_env = |name: &str| std::env::var_os(name).is_some_and(|v| !v.is_empty());
+        if Path::new("/.flatpak-info").exists() || has_env("FLATPAK_ID") {
+            return Sandbox::Flatpak;
+        }
+        if has_env("SNAP") && has_env("SNAP_NAME") {
+            return Sandbox::Snap;
+        }
+        if has_env("APPIMAGE") || has_env("APPDIR") {
+            return Sandbox::AppImage;
+        }
+    }
+    Sandbox::None
+}
+
 /// Get the games directory path
 pub fn get_games_dir() -> Result<PathBuf, String> {
     get_app_dir().map(|p| p.join("Games"))
@@ -190,11 +1040,229 @@ pub fn get_ruffle_dir() -> Result<PathBuf, String> {
     get_app_dir().map(|p| p.join("Ruffle"))
 }
 
+/// Get the directory downloaded Ruffle archives are kept in when
+/// `cache_ruffle_archives` is on
+pub fn get_ruffle_cache_dir() -> Result<PathBuf, String> {
+    get_app_dir().map(|p| p.join("RuffleCache"))
+}
+
+/// Whether files can be created in `dir`, creating it if needed
+pub fn is_dir_writable(dir: &Path) -> bool {
+    if fs::create_dir_all(dir).is_err() {
+        return false;
+    }
+    let probe = dir.join(".write_test");
+    let writable = fs::write(&probe, b"").is_ok();
+    let _ = fs::remove_file(&probe);
+    writable
+}
+
+/// Resolve a user-chosen install location to `<install_dir>/<folder>`, since an
+/// install replaces its whole folder. Returns None, after logging, when there
+/// is no choice or it isn't writable, so the default directory is used instead.
+pub fn custom_install_dir(install_dir: Option<&str>, folder: &str) -> Option<PathBuf> {
+    let dir = PathBuf::from(install_dir?).join(folder);
+    if is_dir_writable(&dir) {
+        Some(dir)
+    } else {
+        crate::logger::log(&format!(
+            "Install directory {} is not writable, using the default",
+            dir.display()
+        ));
+        None
+    }
+}
+
+/// Get the path of the user-editable config.json, which takes precedence over
+/// the bundled one
+pub fn get_user_config_path() -> Result<PathBuf, String> {
+    get_app_dir().map(|p| p.join("config.json"))
+}
+
+/// How many previous versions of the user config.json are kept
+const MAX_CONFIG_BACKUPS: usize = 3;
+
+/// Path of the `n`th newest backup of the user config.json:
+/// `config.json.bak`, then `config.json.bak.1` and so on
+fn config_backup_path(config_path: &Path, n: usize) -> PathBuf {
+    let mut name = config_path.as_os_str().to_owned();
+    name.push(".bak");
+    if n > 0 {
+        name.push(format!(".{}", n));
+    }
+    PathBuf::from(name)
+}
+
+/// Copy the user config.json to `config.json.bak`, shifting older backups
+/// along, up to [`MAX_CONFIG_BACKUPS`]. Nothing is done if it matches the
+/// newest backup.
+///
+/// Backups are taken when the launcher starts with a config.json that loads
+/// (so hand edits made since the last start are kept) and by
+/// [`write_user_config`] before it replaces the file.
+pub fn backup_user_config() -> Result<(), String> {
+    let path = get_user_config_path()?;
+    let Ok(content) = fs::read(&path) else {
+        return Ok(());
+    };
+    if fs::read(config_backup_path(&path, 0)).is_ok_and(|newest| newest == content) {
+        return Ok(());
+    }
+    for n in (1..MAX_CONFIG_BACKUPS).rev() {
+        let older = config_backup_path(&path, n - 1);
+        if older.exists() {
+            fs::rename(&older, config_backup_path(&path, n))
+                .map_err(|e| errors::io_error("Failed to rotate config backups", &e))?;
+        }
+    }
+    fs::write(config_backup_path(&path, 0), content)
+        .map_err(|e| errors::io_error("Failed to back up config.json", &e))
+}
+
+/// Take backup `n` out of the rotation, moving older backups up
+fn drop_config_backup(path: &Path, n: usize) -> Result<(), String> {
+    for i in n + 1..MAX_CONFIG_BACKUPS {
+        let older = config_backup_path(path, i);
+        if older.exists() {
+            fs::rename(&older, config_backup_path(path, i - 1))
+                .map_err(|e| errors::io_error("Failed to rotate config backups", &e))?;
+        } else {
+            let _ = fs::remove_file(config_backup_path(path, i - 1));
+            return Ok(());
+        }
+    }
+    let _ = fs::remove_file(config_backup_path(path, MAX_CONFIG_BACKUPS - 1));
+    Ok(())
+}
+
+/// Write `content` as the user config.json, backing up the current one first
+/// (see [`backup_user_config`])
+pub fn write_user_config(content: &str) -> Result<PathBuf, String> {
+    backup_user_config()?;
+    let path = get_user_config_path()?;
+    fs::write(&path, content).map_err(|e| errors::io_error("Failed to write config.json", &e))?;
+    Ok(path)
+}
+
+/// Put the newest config.json backup back in place. The config being
+/// replaced becomes the newest backup, so a restore can itself be undone.
+/// Returns the restored path.
+pub fn restore_user_config() -> Result<PathBuf, String> {
+    let path = get_user_config_path()?;
+    let newest = config_backup_path(&path, 0);
+    // The startup backup can be a copy of the current config; skip it
+    let current = fs::read(&path).ok();
+    while current.is_some() && fs::read(&newest).ok() == current {
+        drop_config_backup(&path, 0)?;
+    }
+    let content = fs::read_to_string(&newest)
+        .map_err(|_| "There is no previous config.json to restore".to_string())?;
+    let previous = serde_json::from_str::<AppConfig>(&content)
+        .map(AppConfig::migrate_game_urls)
+        .map_err(|e| format!("The previous config.json is invalid: {}", e))?;
+    let errors = validate_config(&previous);
+    if !errors.is_empty() {
+        return Err(format!(
+            "The previous config.json is invalid:\n{}",
+            errors.join("\n")
+        ));
+    }
+
+    // Take the restored backup out of the rotation
+    drop_config_backup(&path, 0)?;
+    write_user_config(&content)
+}
+
+/// Get the game icon cache directory path
+pub fn get_icons_dir() -> Result<PathBuf, String> {
+    get_app_dir().map(|p| p.join("Icons"))
+}
+
+/// Get the Ruffle save data directory from settings, if one is configured
+pub fn get_ruffle_storage_dir(settings: &Settings) -> Result<Option<PathBuf>, String> {
+    match &settings.ruffle_storage_dir {
+        Some(dir) if !dir.trim().is_empty() => {
+            let dir = PathBuf::from(dir);
+            if dir.is_absolute() {
+                Ok(Some(dir))
+            } else {
+                get_app_dir().map(|p| Some(p.join(dir)))
+            }
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Where Ruffle stores save data when `ruffle_storage_dir` isn't set, which
+/// is Ruffle's own default
+pub fn get_ruffle_default_storage_dir() -> Result<PathBuf, String> {
+    dirs::data_local_dir()
+        .map(|p| p.join("ruffle").join("SharedObjects"))
+        .ok_or_else(|| "Failed to get local data directory".to_string())
+}
+
+/// The directory Ruffle keeps save data in: `ruffle_storage_dir` when set,
+/// otherwise Ruffle's default
+pub fn resolve_ruffle_storage_dir(settings: &Settings) -> Result<PathBuf, String> {
+    match get_ruffle_storage_dir(settings)? {
+        Some(dir) => Ok(dir),
+        None => get_ruffle_default_storage_dir(),
+    }
+}
+
+/// Where the Flash Player projector stores SharedObjects for the current user
+pub fn get_flash_shared_objects_dir() -> Result<PathBuf, String> {
+    #[cfg(target_os = "windows")]
+    let dir = dirs::data_dir().map(|p| p.join("Macromedia").join("Flash Player"));
+    #[cfg(target_os = "macos")]
+    let dir = dirs::home_dir().map(|p| {
+        p.join("Library")
+            .join("Preferences")
+            .join("Macromedia")
+            .join("Flash Player")
+    });
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let dir = dirs::home_dir().map(|p| p.join(".macromedia").join("Flash_Player"));
+
+    dir.map(|p| p.join("#SharedObjects"))
+        .ok_or_else(|| "Failed to get home directory".to_string())
+}
+
+/// Get the directory saves are backed up to before an import replaces them
+pub fn get_save_backups_dir() -> Result<PathBuf, String> {
+    get_app_dir().map(|p| p.join("SaveBackups"))
+}
+
 /// Load the bundled config.json (app configuration)
 pub fn load_config(config_path: &PathBuf) -> Result<AppConfig, String> {
     let content = fs::read_to_string(config_path)
         .map_err(|e| format!("Failed to read config.json: {}", e))?;
-    serde_json::from_str(&content).map_err(|e| format!("Failed to parse config.json: {}", e))
+    serde_json::from_str::<AppConfig>(&content)
+        .map(AppConfig::migrate_game_urls)
+        .map(|config| AppConfig {
+            source_path: Some(config_path.clone()),
+            ..config
+        })
+        .map_err(|e| format!("Failed to parse config.json: {}", e))
+}
+
+/// Lock the cached version.json contents managed as app state
+pub fn lock_versions(versions: &Mutex<GameVersions>) -> MutexGuard<'_, GameVersions> {
+    versions
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Change the cached versions and write them through to version.json,
+/// holding the lock so concurrent updates can't overwrite each other
+pub fn update_versions<T>(
+    versions: &Mutex<GameVersions>,
+    update: impl FnOnce(&mut GameVersions) -> T,
+) -> Result<T, String> {
+    let mut versions = lock_versions(versions);
+    let result = update(&mut versions);
+    save_versions(&versions)?;
+    Ok(result)
 }
 
 /// Load version information from version.json
@@ -216,12 +1284,13 @@ pub fn load_versions() -> Result<GameVersions, String> {
 pub fn save_versions(versions: &GameVersions) -> Result<(), String> {
     let games_dir = get_games_dir()?;
     fs::create_dir_all(&games_dir)
-        .map_err(|e| format!("Failed to create games directory: {}", e))?;
+        .map_err(|e| errors::io_error("Failed to create games directory", &e))?;
 
     let version_path = games_dir.join("version.json");
     let content = serde_json::to_string_pretty(versions)
         .map_err(|e| format!("Failed to serialize versions: {}", e))?;
-    fs::write(&version_path, content).map_err(|e| format!("Failed to write version.json: {}", e))
+    fs::write(&version_path, content)
+        .map_err(|e| errors::io_error("Failed to write version.json", &e))
 }
 
 /// Load user settings from settings.json
@@ -242,39 +1311,42 @@ pub fn load_settings() -> Result<Settings, String> {
 pub fn save_settings(settings: &Settings) -> Result<(), String> {
     let flash_dir = get_flash_dir()?;
     fs::create_dir_all(&flash_dir)
-        .map_err(|e| format!("Failed to create flash directory: {}", e))?;
+        .map_err(|e| errors::io_error("Failed to create flash directory", &e))?;
 
     let settings_path = flash_dir.join("settings.json");
     let content = serde_json::to_string_pretty(settings)
         .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-    fs::write(&settings_path, content).map_err(|e| format!("Failed to write settings.json: {}", e))
+    fs::write(&settings_path, content)
+        .map_err(|e| errors::io_error("Failed to write settings.json", &e))
 }
 
-/// Initialize the application directories and configuration
-pub fn init_config() -> Result<(), String> {
-    let games_dir = get_games_dir()?;
-    let flash_dir = get_flash_dir()?;
-
-    fs::create_dir_all(&games_dir)
-        .map_err(|e| format!("Failed to create games directory: {}", e))?;
-    fs::create_dir_all(&flash_dir)
-        .map_err(|e| format!("Failed to create flash directory: {}", e))?;
+/// Initialize the application directories and configuration.
+/// Returns the directories that were missing and had to be created.
+pub fn init_config() -> Result<Vec<PathBuf>, String> {
+    let dirs = [
+        ("games", get_games_dir()?),
+        ("flash", get_flash_dir()?),
+        ("ruffle", get_ruffle_dir()?),
+    ];
 
-    let ruffle_dir = get_ruffle_dir()?;
-    fs::create_dir_all(&ruffle_dir)
-        .map_err(|e| format!("Failed to create ruffle directory: {}", e))?;
+    let mut created = Vec::new();
+    for (name, dir) in dirs {
+        if !dir.exists() {
+            fs::create_dir_all(&dir)
+                .map_err(|e| format!("Failed to create {} directory: {}", name, e))?;
+            created.push(dir);
+        }
+    }
+    crate::disk::warn_if_low(&get_app_dir()?);
 
-    Ok(())
+    Ok(created)
 }
 
 /// Get the flash player executable path based on OS and settings
 pub fn get_flash_player_path(config: &AppConfig, settings: &Settings) -> Result<PathBuf, String> {
     // Check for custom path first
-    if let Some(custom_path) = &settings.flash_player_path {
-        let path = PathBuf::from(custom_path);
-        if path.exists() {
-            return Ok(path);
-        }
+    if let Some(path) = existing_custom_path(settings.flash_player_path.as_deref()) {
+        return Ok(resolve_symlink(path));
     }
 
     // Use default path based on OS
@@ -287,19 +1359,38 @@ pub fn get_flash_player_path(config: &AppConfig, settings: &Settings) -> Result<
     let filename = &config.flash_player.macos.filename;
 
     #[cfg(target_os = "linux")]
-    let filename = &config.flash_player.linux.filename;
+    let filename = if settings.use_wine_flash.unwrap_or(false) {
+        &config.flash_player.windows.filename
+    } else {
+        &config.flash_player.linux.filename
+    };
+
+    Ok(resolve_symlink(flash_dir.join(filename)))
+}
 
-    Ok(flash_dir.join(filename))
+/// A custom player path from settings, if it is set and exists. Custom paths
+/// that don't exist are ignored in favour of the managed install.
+pub fn existing_custom_path(custom_path: Option<&str>) -> Option<PathBuf> {
+    custom_path.map(PathBuf::from).filter(|p| p.exists())
+}
+
+/// Follow a symlinked player to its target, keeping the link path if it dangles
+fn resolve_symlink(path: PathBuf) -> PathBuf {
+    let is_symlink = fs::symlink_metadata(&path)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false);
+    if is_symlink {
+        fs::canonicalize(&path).unwrap_or(path)
+    } else {
+        path
+    }
 }
 
 /// Get the ruffle executable path based on OS and settings
 pub fn get_ruffle_path(config: &AppConfig, settings: &Settings) -> Result<PathBuf, String> {
     // Check for custom path first
-    if let Some(custom_path) = &settings.ruffle_path {
-        let path = PathBuf::from(custom_path);
-        if path.exists() {
-            return Ok(path);
-        }
+    if let Some(path) = existing_custom_path(settings.ruffle_path.as_deref()) {
+        return Ok(path);
     }
 
     // Use default path based on OS
@@ -316,3 +1407,100 @@ pub fn get_ruffle_path(config: &AppConfig, settings: &Settings) -> Result<PathBu
 
     Ok(ruffle_dir.join(filename))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_bundled_keeps_loaded_entries_and_adds_new_games() {
+        let mut loaded = AppConfig::default();
+        loaded.games.retain(|id, _| id == "PTD1");
+        loaded.games.get_mut("PTD1").unwrap().url = "https://mirror.example/ptd1.swf".to_string();
+
+        let mut bundled = AppConfig::default();
+        let mut ptd4 = bundled.games["PTD3"].clone();
+        ptd4.url = "https://ptd.onl/ptd4-latest.swf".to_string();
+        ptd4.display_name = "PTD 4".to_string();
+        bundled.games.insert("PTD4".to_string(), ptd4);
+
+        let merged = loaded.merge_bundled(&bundled);
+
+        assert_eq!(merged.games["PTD1"].url, "https://mirror.example/ptd1.swf");
+        assert_eq!(merged.games["PTD4"].display_name, "PTD 4");
+        assert_eq!(merged.games["PTD2"].url, bundled.games["PTD2"].url);
+        assert_eq!(merged.games.len(), bundled.games.len());
+    }
+
+    fn error_fields(settings: &Settings) -> Vec<String> {
+        settings.validate().into_iter().map(|e| e.field).collect()
+    }
+
+    #[test]
+    fn validate_ruffle_window_size_bounds() {
+        let width = |w| Settings {
+            ruffle_width: Some(w),
+            ..Default::default()
+        };
+        assert_eq!(error_fields(&width(0)), ["ruffle_width"]);
+        assert!(error_fields(&width(MAX_RUFFLE_WINDOW_SIZE)).is_empty());
+        assert_eq!(
+            error_fields(&width(MAX_RUFFLE_WINDOW_SIZE + 1)),
+            ["ruffle_width"]
+        );
+    }
+
+    #[test]
+    fn validate_release_fetch_timeout_bounds() {
+        let timeout = |secs| Settings {
+            release_fetch_timeout_secs: Some(secs),
+            ..Default::default()
+        };
+        assert_eq!(error_fields(&timeout(0)), ["release_fetch_timeout_secs"]);
+        assert!(error_fields(&timeout(MAX_RELEASE_FETCH_TIMEOUT_SECS)).is_empty());
+        assert_eq!(
+            error_fields(&timeout(MAX_RELEASE_FETCH_TIMEOUT_SECS + 1)),
+            ["release_fetch_timeout_secs"]
+        );
+    }
+
+    #[test]
+    fn validate_critical_launch_env_needs_override() {
+        let mut settings = Settings {
+            launch_env: Some(HashMap::from([("PATH".to_string(), "/tmp".to_string())])),
+            ..Default::default()
+        };
+        assert_eq!(error_fields(&settings), ["launch_env"]);
+
+        settings.launch_env_override_critical = Some(true);
+        assert!(error_fields(&settings).is_empty());
+    }
+
+    #[test]
+    fn validate_collects_every_error() {
+        let settings = Settings {
+            ruffle_width: Some(0),
+            release_fetch_timeout_secs: Some(MAX_RELEASE_FETCH_TIMEOUT_SECS + 1),
+            launch_env: Some(HashMap::from([("PATH".to_string(), "/tmp".to_string())])),
+            ..Default::default()
+        };
+        let fields = error_fields(&settings);
+        assert_eq!(fields.len(), 3);
+        for field in ["ruffle_width", "release_fetch_timeout_secs", "launch_env"] {
+            assert!(fields.iter().any(|f| f == field), "missing {}", field);
+        }
+    }
+
+    #[test]
+    fn missing_game_flash_path_is_only_a_warning() {
+        let settings = Settings {
+            game_flash_paths: Some(HashMap::from([(
+                "PTD1".to_string(),
+                "/nonexistent/flashplayer".to_string(),
+            )])),
+            ..Default::default()
+        };
+        assert!(settings.validate().is_empty());
+        assert_eq!(settings.warnings().len(), 1);
+    }
+}
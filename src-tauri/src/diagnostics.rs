@@ -0,0 +1,730 @@
+//! Environment report for troubleshooting, and support bundles that package it
+//! with the launcher log and settings.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::breaker::{HostBreakers, HostCircuit};
+use crate::config::{self, AppConfig, GameVersions, Sandbox, Settings};
+use crate::{errors, game, logger};
+
+#[derive(serde::Serialize)]
+pub struct DirectoryCheck {
+    pub path: String,
+    pub exists: bool,
+    pub writable: bool,
+}
+
+#[derive(serde::Serialize)]
+pub struct PlayerCheck {
+    pub path: Option<String>,
+    /// The path comes from settings rather than the managed install
+    pub custom: bool,
+    pub installed: bool,
+    pub version: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct Diagnostics {
+    pub launcher_version: String,
+    pub os: String,
+    pub arch: String,
+    /// Linux packaging sandbox, which restricts file and process access
+    pub sandbox: Sandbox,
+    /// "flash" or "ruffle"
+    pub active_player: String,
+    pub directories: BTreeMap<String, DirectoryCheck>,
+    pub flash: PlayerCheck,
+    pub ruffle: PlayerCheck,
+    /// Downloaded game ids and their paths
+    pub installed_games: BTreeMap<String, String>,
+    /// Checks of the custom player paths set in settings
+    pub player_paths: Vec<PlayerPathCheck>,
+    /// Download hosts with recent failures, including ones being skipped
+    pub host_circuits: Vec<HostCircuit>,
+    pub log_path: Option<String>,
+}
+
+#[derive(serde::Serialize)]
+pub struct PlayerPathCheck {
+    /// "flash" or "ruffle"
+    pub player: String,
+    pub path: String,
+    /// Why the path doesn't look like the expected player; `None` if it does
+    pub problem: Option<String>,
+    /// Whether the problem should stop a launch. A file name that merely
+    /// doesn't look like the player is only a warning.
+    pub blocking: bool,
+}
+
+/// How long `ruffle --version` may take before the path is reported as not Ruffle
+const RUFFLE_VERSION_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Check that the custom `flash_player_path` and `ruffle_path` point at the
+/// right players. Ruffle is asked for `--version`; Flash can't be run without
+/// opening a window, so its file name is checked instead.
+pub fn check_player_paths(settings: &Settings) -> Vec<PlayerPathCheck> {
+    let mut checks = Vec::new();
+
+    if let Some(path) = &settings.flash_player_path {
+        let name = file_name_lowercase(path);
+        let (problem, blocking) = if !Path::new(path).exists() {
+            (
+                Some("Path does not exist, so the managed Flash Player is used".to_string()),
+                true,
+            )
+        } else if settings.ruffle_path.as_ref() == Some(path) {
+            (
+                Some("Same path as the custom Ruffle path".to_string()),
+                true,
+            )
+        } else if name.contains("ruffle") {
+            (
+                Some("Path looks like Ruffle, not Flash Player".to_string()),
+                true,
+            )
+        } else if !name.contains("flash") {
+            (
+                Some("File name doesn't look like a Flash Player projector".to_string()),
+                false,
+            )
+        } else {
+            (None, false)
+        };
+        checks.push(PlayerPathCheck {
+            player: "flash".to_string(),
+            path: path.clone(),
+            problem,
+            blocking,
+        });
+    }
+
+    if let Some(path) = &settings.ruffle_path {
+        let problem = if !Path::new(path).exists() {
+            Some("Path does not exist, so the managed Ruffle is used".to_string())
+        } else if file_name_lowercase(path).contains("flash") {
+            Some("Path looks like Flash Player, not Ruffle".to_string())
+        } else {
+            ruffle_version(Path::new(path)).err()
+        };
+        checks.push(PlayerPathCheck {
+            player: "ruffle".to_string(),
+            path: path.clone(),
+            blocking: problem.is_some(),
+            problem,
+        });
+    }
+
+    checks
+}
+
+fn file_name_lowercase(path: &str) -> String {
+    Path::new(path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Run `ruffle --version` and return its version line if it identifies as
+/// Ruffle
+fn ruffle_version(path: &Path) -> Result<String, String> {
+    let mut child = Command::new(path)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| errors::spawn_error("Failed to run", path, &e))?;
+
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if started.elapsed() < RUFFLE_VERSION_TIMEOUT => {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err("Did not answer --version, so it is probably not Ruffle".to_string());
+            }
+            Err(e) => return Err(format!("Failed to run: {}", e)),
+        }
+    }
+
+    let mut output = String::new();
+    if let Some(mut stdout) = child.stdout.take() {
+        let _ = stdout.read_to_string(&mut output);
+    }
+    if output.to_lowercase().contains("ruffle") {
+        Ok(output.lines().next().unwrap_or_default().trim().to_string())
+    } else {
+        Err("--version output doesn't mention Ruffle".to_string())
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct PlayerTest {
+    /// The player could be started
+    pub started: bool,
+    /// Version line from `ruffle --version`; Flash projectors don't report one
+    pub version: Option<String>,
+    /// Why the player couldn't be started
+    pub error: Option<String>,
+}
+
+/// How long a Flash projector is left running to confirm it starts
+const FLASH_TEST_DURATION: Duration = Duration::from_secs(1);
+
+/// Start the player at `path` to confirm it runs. Ruffle is asked for
+/// `--version`; a Flash projector has no harmless option, so it is started
+/// without a movie and stopped again after a moment.
+pub fn test_player_path(
+    path: &Path,
+    kind: &str,
+    settings: &Settings,
+) -> Result<PlayerTest, String> {
+    let result = match kind {
+        "ruffle" => ruffle_version(path).map(Some),
+        "flash" => start_flash_projector(path, settings).map(|_| None),
+        other => {
+            return Err(format!(
+                "Unknown player '{}'. Expected 'flash' or 'ruffle'",
+                other
+            ))
+        }
+    };
+    Ok(match result {
+        Ok(version) => PlayerTest {
+            started: true,
+            version,
+            error: None,
+        },
+        Err(e) => PlayerTest {
+            started: false,
+            version: None,
+            error: Some(e),
+        },
+    })
+}
+
+// `settings` only matters for Wine on Linux
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+fn start_flash_projector(path: &Path, settings: &Settings) -> Result<(), String> {
+    if !path.exists() {
+        return Err("Path does not exist".to_string());
+    }
+
+    // An .app bundle can't be run directly; start the executable inside it
+    #[cfg(target_os = "macos")]
+    let executable = if path.is_dir() {
+        crate::flash::bundle_executable(path)?
+    } else {
+        path.to_path_buf()
+    };
+    #[cfg(not(target_os = "macos"))]
+    let executable = path.to_path_buf();
+
+    #[cfg(target_os = "linux")]
+    let mut cmd = if settings.use_wine_flash.unwrap_or(false) {
+        let mut cmd = Command::new(crate::flash::find_wine()?);
+        cmd.arg(&executable);
+        cmd
+    } else {
+        Command::new(&executable)
+    };
+    #[cfg(not(target_os = "linux"))]
+    let mut cmd = Command::new(&executable);
+
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| errors::spawn_error("Failed to run", &executable, &e))?;
+
+    std::thread::sleep(FLASH_TEST_DURATION);
+    match child.try_wait() {
+        Ok(None) => {
+            let _ = child.kill();
+            let _ = child.wait();
+            Ok(())
+        }
+        Ok(Some(status)) if status.success() => Ok(()),
+        Ok(Some(status)) => Err(format!("Exited right after starting ({})", status)),
+        Err(e) => Err(format!("Failed to run: {}", e)),
+    }
+}
+
+/// Check that the player binary at `path` ("flash" or "ruffle" as `kind`)
+/// actually starts, returning the version Ruffle reports
+#[tauri::command(async)]
+pub fn test_player(
+    path: String,
+    kind: String,
+    settings: tauri::State<'_, Mutex<Settings>>,
+) -> Result<PlayerTest, String> {
+    let settings = config::lock_settings(&settings).clone();
+    test_player_path(Path::new(&path), &kind, &settings)
+}
+
+/// Contents of `ruffle.log`, written while `ruffle_log_level` is set; empty if
+/// there is none yet
+#[tauri::command(async)]
+pub fn get_ruffle_log() -> Result<String, String> {
+    let path = logger::get_ruffle_log_path()?;
+    match fs::read(&path) {
+        Ok(bytes) => Ok(String::from_utf8_lossy(&bytes).into_owned()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(String::new()),
+        Err(e) => Err(format!("Failed to read Ruffle log: {}", e)),
+    }
+}
+
+/// Report custom player paths that don't point at the expected player
+#[tauri::command(async)]
+pub fn validate_player_paths(settings: tauri::State<'_, Mutex<Settings>>) -> Vec<PlayerPathCheck> {
+    let settings = config::lock_settings(&settings).clone();
+    check_player_paths(&settings)
+}
+
+/// Gather the diagnostics report
+pub fn collect(
+    app_config: &AppConfig,
+    settings: &Settings,
+    versions: GameVersions,
+    breakers: &HostBreakers,
+) -> Diagnostics {
+    let mut directories = BTreeMap::new();
+    let dirs = [
+        ("app", config::get_app_dir()),
+        ("games", config::get_games_dir()),
+        ("flash", config::get_flash_dir()),
+        ("ruffle", config::get_ruffle_dir()),
+    ];
+    for (name, dir) in dirs {
+        if let Ok(dir) = dir {
+            directories.insert(name.to_string(), check_directory(&dir));
+        }
+    }
+
+    let flash = check_player(
+        config::get_flash_player_path(app_config, settings),
+        config::existing_custom_path(settings.flash_player_path.as_deref()).is_some(),
+        versions.flash_player,
+    );
+    let ruffle = check_player(
+        config::get_ruffle_path(app_config, settings),
+        config::existing_custom_path(settings.ruffle_path.as_deref()).is_some(),
+        versions.ruffle,
+    );
+
+    let installed_games = app_config
+        .game_ids()
+        .into_iter()
+        .filter_map(|id| {
+            let path = game::find_game_path(id).ok().flatten()?;
+            Some((id.clone(), path.to_string_lossy().into_owned()))
+        })
+        .collect();
+
+    Diagnostics {
+        launcher_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        sandbox: config::detect_sandbox(),
+        active_player: if settings.use_ruffle.unwrap_or(false) {
+            "ruffle"
+        } else {
+            "flash"
+        }
+        .to_string(),
+        directories,
+        flash,
+        ruffle,
+        installed_games,
+        player_paths: check_player_paths(settings),
+        host_circuits: breakers.snapshot(),
+        log_path: logger::get_log_path()
+            .ok()
+            .map(|p| p.to_string_lossy().into_owned()),
+    }
+}
+
+fn check_directory(dir: &Path) -> DirectoryCheck {
+    let exists = dir.is_dir();
+    DirectoryCheck {
+        path: dir.to_string_lossy().into_owned(),
+        exists,
+        // Only probe existing directories so diagnostics don't create them
+        writable: exists && config::is_dir_writable(dir),
+    }
+}
+
+fn check_player(path: Result<PathBuf, String>, custom: bool, version: String) -> PlayerCheck {
+    let path = path.ok();
+    PlayerCheck {
+        installed: path.as_ref().is_some_and(|p| p.exists()),
+        path: path.map(|p| p.to_string_lossy().into_owned()),
+        custom,
+        version,
+    }
+}
+
+/// Where the launcher gets a player or game from
+#[derive(serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallSource {
+    /// A path set in settings
+    Custom,
+    /// The launcher's own install directory
+    Managed,
+}
+
+#[derive(serde::Serialize)]
+pub struct InstallEntry {
+    pub source: InstallSource,
+    /// Absolute path that will be used
+    pub path: Option<String>,
+    pub exists: bool,
+    /// Version recorded in `version.json`, empty if unknown
+    pub version: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct InstallReport {
+    pub flash: InstallEntry,
+    pub ruffle: InstallEntry,
+    pub games: BTreeMap<String, InstallEntry>,
+}
+
+fn install_entry(path: Result<PathBuf, String>, custom: bool, version: String) -> InstallEntry {
+    let path = path.ok().map(|p| std::path::absolute(&p).unwrap_or(p));
+    InstallEntry {
+        source: if custom {
+            InstallSource::Custom
+        } else {
+            InstallSource::Managed
+        },
+        exists: path.as_ref().is_some_and(|p| p.exists()),
+        path: path.map(|p| p.to_string_lossy().into_owned()),
+        version,
+    }
+}
+
+/// Report which player and game files the launcher will use, whether each
+/// comes from a custom path in settings or the managed install, and whether
+/// it exists. Custom player paths that don't exist fall back to the managed
+/// install, as they do at launch.
+#[tauri::command]
+pub fn get_install_report(
+    app_config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
+) -> InstallReport {
+    let settings = config::lock_settings(&settings).clone();
+    let mut versions = config::lock_versions(&versions).clone();
+
+    let flash = install_entry(
+        config::get_flash_player_path(&app_config, &settings),
+        config::existing_custom_path(settings.flash_player_path.as_deref()).is_some(),
+        versions.flash_player,
+    );
+    let ruffle = install_entry(
+        config::get_ruffle_path(&app_config, &settings),
+        config::existing_custom_path(settings.ruffle_path.as_deref()).is_some(),
+        versions.ruffle,
+    );
+
+    let games = app_config
+        .game_ids()
+        .into_iter()
+        .map(|id| {
+            // Not downloaded yet: report where the download will go
+            let path = match game::find_game_path(id) {
+                Ok(Some(path)) => Ok(path),
+                Ok(None) => game::game_dir(id, &settings).map(|d| d.join(format!("{}.swf", id))),
+                Err(e) => Err(e),
+            };
+            let version = versions
+                .games
+                .remove(id.as_str())
+                .map(|v| v.version)
+                .unwrap_or_default();
+            (id.clone(), install_entry(path, false, version))
+        })
+        .collect();
+
+    InstallReport {
+        flash,
+        ruffle,
+        games,
+    }
+}
+
+/// Report on the launcher's directories, players and games for troubleshooting
+#[tauri::command(async)]
+pub fn run_diagnostics(
+    app_config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
+    breakers: tauri::State<'_, HostBreakers>,
+) -> Diagnostics {
+    let settings = config::lock_settings(&settings).clone();
+    let versions = config::lock_versions(&versions).clone();
+    collect(&app_config, &settings, versions, &breakers)
+}
+
+/// How long each host in `check_connectivity` gets to answer
+const CONNECTIVITY_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(serde::Serialize)]
+pub struct HostReachability {
+    pub host: String,
+    /// URL that was requested
+    pub url: String,
+    /// The host answered, with any HTTP status
+    pub reachable: bool,
+    pub latency_ms: Option<u64>,
+    /// HTTP status of the answer
+    pub status: Option<u16>,
+    /// Why the host couldn't be reached
+    pub error: Option<String>,
+}
+
+/// One URL per download host: games, Flash Player and Ruffle downloads for
+/// every platform, and the GitHub API and its configured mirrors
+fn download_hosts(app_config: &AppConfig, settings: &Settings) -> Vec<(String, String)> {
+    let flash = &app_config.flash_player;
+    let ruffle = &app_config.ruffle;
+    let repo = settings
+        .ruffle_repo
+        .clone()
+        .unwrap_or_else(|| config::DEFAULT_RUFFLE_REPO.to_string());
+
+    let mut urls: Vec<String> = app_config
+        .game_ids()
+        .into_iter()
+        .filter_map(|id| app_config.resolve_game_url(id, settings))
+        .collect();
+    for os in [&flash.windows, &flash.macos, &flash.linux] {
+        urls.push(os.primary_url.clone());
+        urls.extend(os.fallback_url.clone());
+    }
+    for os in [&ruffle.windows, &ruffle.macos, &ruffle.linux] {
+        urls.push(os.url.clone());
+    }
+    urls.push(format!("https://api.github.com/repos/{}/releases", repo));
+    for mirror in settings.ruffle_metadata_mirrors.iter().flatten() {
+        urls.push(config::mirror_url(mirror, &repo));
+    }
+
+    let mut hosts: Vec<(String, String)> = Vec::new();
+    for url in urls {
+        let Some(host) = reqwest::Url::parse(&url)
+            .ok()
+            .and_then(|u| u.host_str().map(|h| h.to_lowercase()))
+        else {
+            continue;
+        };
+        if !hosts.iter().any(|(known, _)| *known == host) {
+            hosts.push((host, url));
+        }
+    }
+    hosts.sort();
+    hosts
+}
+
+/// Send a HEAD request to every download host at once and report which ones
+/// answer. Any HTTP answer counts as reachable, since some hosts reject HEAD.
+#[tauri::command]
+pub async fn check_connectivity(
+    app_config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+) -> Result<Vec<HostReachability>, String> {
+    let hosts = download_hosts(&app_config, &config::lock_settings(&settings));
+    let client = crate::download::client_builder(&settings)?
+        .user_agent("PTDLauncher")
+        .timeout(CONNECTIVITY_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+    Ok(
+        futures_util::future::join_all(hosts.into_iter().map(|(host, url)| {
+            let client = &client;
+            async move {
+                let started = Instant::now();
+                let result = client.head(&url).send().await;
+                let latency_ms = started.elapsed().as_millis() as u64;
+                let url = config::redact_url(&url);
+                match result {
+                    Ok(response) => HostReachability {
+                        host,
+                        url,
+                        reachable: true,
+                        latency_ms: Some(latency_ms),
+                        status: Some(response.status().as_u16()),
+                        error: None,
+                    },
+                    Err(e) => HostReachability {
+                        host,
+                        url,
+                        reachable: false,
+                        latency_ms: None,
+                        status: None,
+                        error: Some(e.without_url().to_string()),
+                    },
+                }
+            }
+        }))
+        .await,
+    )
+}
+
+/// The Linux packaging sandbox the launcher runs in: "none", "flatpak",
+/// "snap" or "app_image"
+#[tauri::command]
+pub fn detect_sandbox() -> Sandbox {
+    config::detect_sandbox()
+}
+
+#[derive(serde::Serialize)]
+pub struct VersionInfo {
+    pub launcher_version: String,
+    pub tauri_version: String,
+    /// Commit the launcher was built from, if it was built from a git checkout
+    pub git_commit: Option<String>,
+    /// Installed Flash Player version, empty if unknown
+    pub flash_version: String,
+    /// Installed Ruffle version, empty if unknown
+    pub ruffle_version: String,
+}
+
+/// The launcher build and installed player versions, for bug reports
+#[tauri::command]
+pub fn get_version_info(versions: tauri::State<'_, Mutex<GameVersions>>) -> VersionInfo {
+    let versions = config::lock_versions(&versions);
+    VersionInfo {
+        launcher_version: env!("CARGO_PKG_VERSION").to_string(),
+        tauri_version: tauri::VERSION.to_string(),
+        git_commit: option_env!("PTD_GIT_COMMIT").map(|c| c.to_string()),
+        flash_version: versions.flash_player.clone(),
+        ruffle_version: versions.ruffle.clone(),
+    }
+}
+
+/// Settings as JSON with anything that might be a secret removed: values of
+/// token, password, secret and proxy fields, and credentials embedded in URLs
+fn redacted_settings(settings: &Settings) -> serde_json::Value {
+    fn redact(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    let key = key.to_lowercase();
+                    if key.contains("token")
+                        || key.contains("password")
+                        || key.contains("secret")
+                        || key.contains("proxy")
+                    {
+                        *value = serde_json::Value::String("[redacted]".to_string());
+                    } else {
+                        redact(value);
+                    }
+                }
+            }
+            serde_json::Value::Array(items) => items.iter_mut().for_each(redact),
+            serde_json::Value::String(s) if s.contains("://") => {
+                *s = config::redact_url(s);
+            }
+            _ => {}
+        }
+    }
+
+    let mut value = serde_json::to_value(settings).unwrap_or_default();
+    redact(&mut value);
+    value
+}
+
+/// Zip the launcher log, redacted settings, `version.json` and a diagnostics
+/// report into one file for attaching to bug reports. `dest_path` may be a
+/// directory, in which case a timestamped file name is used. Returns the path
+/// of the created bundle.
+#[tauri::command(async)]
+pub fn create_support_bundle(
+    dest_path: String,
+    app_config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
+    breakers: tauri::State<'_, HostBreakers>,
+) -> Result<String, String> {
+    let mut bundle_path = PathBuf::from(dest_path);
+    if bundle_path.is_dir() {
+        bundle_path = bundle_path.join(format!(
+            "ptd-launcher-support-{}.zip",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        ));
+    }
+
+    let settings = config::lock_settings(&settings).clone();
+    let mut entries: Vec<(String, Vec<u8>)> = Vec::new();
+
+    let diagnostics = collect(
+        &app_config,
+        &settings,
+        config::lock_versions(&versions).clone(),
+        &breakers,
+    );
+    entries.push((
+        "diagnostics.json".to_string(),
+        serde_json::to_vec_pretty(&diagnostics)
+            .map_err(|e| format!("Failed to serialize diagnostics: {}", e))?,
+    ));
+
+    // The saved settings, which is what the launcher will use on next start
+    let saved_settings = config::load_settings().unwrap_or(settings);
+    entries.push((
+        "settings.json".to_string(),
+        serde_json::to_vec_pretty(&redacted_settings(&saved_settings))
+            .map_err(|e| format!("Failed to serialize settings: {}", e))?,
+    ));
+
+    if let Ok(games_dir) = config::get_games_dir() {
+        if let Ok(versions) = fs::read(games_dir.join("version.json")) {
+            entries.push(("version.json".to_string(), versions));
+        }
+    }
+
+    if let Ok(log_path) = logger::get_log_path() {
+        for path in [log_path.with_extension("log.old"), log_path] {
+            if let Ok(log) = fs::read(&path) {
+                let name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                entries.push((name, log));
+            }
+        }
+    }
+
+    let file = fs::File::create(&bundle_path)
+        .map_err(|e| format!("Failed to create support bundle: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default();
+    for (name, contents) in entries {
+        zip.start_file(name, options)
+            .map_err(|e| format!("Failed to write support bundle: {}", e))?;
+        zip.write_all(&contents)
+            .map_err(|e| format!("Failed to write support bundle: {}", e))?;
+    }
+    zip.finish()
+        .map_err(|e| format!("Failed to write support bundle: {}", e))?;
+
+    bundle_path
+        .to_str()
+        .map(|s| s.to_string())
+        .ok_or_else(|| "Invalid path".to_string())
+}
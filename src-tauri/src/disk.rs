@@ -0,0 +1,95 @@
+//! Free space on the launcher's data volume, checked before downloads so a
+//! full disk is reported up front instead of part way through.
+
+use std::path::Path;
+
+use crate::{config, logger};
+
+/// Free space below which startup logs a warning
+const CRITICAL_FREE_SPACE: u64 = 200 * 1024 * 1024; // 200 MB
+
+/// Space left over after a download, so it doesn't fill the disk completely
+const DOWNLOAD_HEADROOM: u64 = 50 * 1024 * 1024; // 50 MB
+
+/// Bytes available to the launcher on the volume holding `path`. A path that
+/// doesn't exist yet is checked through its nearest existing ancestor.
+pub fn available_space(path: &Path) -> Result<u64, String> {
+    let existing = path
+        .ancestors()
+        .find(|p| p.exists())
+        .ok_or_else(|| format!("No existing folder above {}", path.display()))?;
+    fs2::available_space(existing).map_err(|e| {
+        format!(
+            "Failed to check free space on {}: {}",
+            existing.display(),
+            e
+        )
+    })
+}
+
+/// Log a warning when the data volume is nearly full
+pub fn warn_if_low(dir: &Path) {
+    match available_space(dir) {
+        Ok(free) if free < CRITICAL_FREE_SPACE => logger::log(&format!(
+            "Warning: only {} MB free on the drive holding {}",
+            free / (1024 * 1024),
+            dir.display()
+        )),
+        Ok(_) => {}
+        Err(e) => logger::log(&format!("Warning: {}", e)),
+    }
+}
+
+/// Refuse a download of `needed` bytes into `dir` when the volume doesn't
+/// have room for it. If free space can't be determined the download goes
+/// ahead.
+pub fn ensure_space(dir: &Path, needed: u64) -> Result<(), String> {
+    let free = match available_space(dir) {
+        Ok(free) => free,
+        Err(e) => {
+            logger::log(&format!("Warning: {}", e));
+            return Ok(());
+        }
+    };
+    if free < needed.saturating_add(DOWNLOAD_HEADROOM) {
+        return Err(format!(
+            "Not enough disk space: the download needs {} MB but only {} MB is free on the \
+             drive holding {}. Free up some space and try again.",
+            needed.div_ceil(1024 * 1024),
+            free / (1024 * 1024),
+            dir.display()
+        ));
+    }
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+pub struct LowDiskStatus {
+    /// The launcher's data directory
+    pub path: String,
+    /// `None` when the volume couldn't be checked
+    pub free_bytes: Option<u64>,
+    pub threshold_bytes: u64,
+    /// Free space is below the threshold
+    pub low: bool,
+    pub error: Option<String>,
+}
+
+/// Whether the volume holding the launcher's data has less than
+/// `threshold_mb` megabytes free
+#[tauri::command]
+pub fn check_low_disk(threshold_mb: u64) -> Result<LowDiskStatus, String> {
+    let dir = config::get_app_dir()?;
+    let threshold_bytes = threshold_mb.saturating_mul(1024 * 1024);
+    let (free_bytes, error) = match available_space(&dir) {
+        Ok(free) => (Some(free), None),
+        Err(e) => (None, Some(e)),
+    };
+    Ok(LowDiskStatus {
+        path: dir.to_string_lossy().into_owned(),
+        free_bytes,
+        threshold_bytes,
+        low: free_bytes.is_some_and(|free| free < threshold_bytes),
+        error,
+    })
+}
@@ -0,0 +1,952 @@
+//! Shared download helpers used by the flash, ruffle and game modules.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager, Window};
+use tokio_util::sync::CancellationToken;
+
+use crate::breaker::HostBreakers;
+use crate::config::{self, AppConfig, GameVersions, Settings};
+use crate::{disk, errors, logger, queue, tls};
+
+/// Stage of a download, so the UI doesn't have to match on `status` text
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DownloadPhase {
+    Connecting,
+    Downloading,
+    Extracting,
+    Verifying,
+    Complete,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct DownloadProgress {
+    pub item: String,
+    pub progress: u32,
+    pub downloaded: u64,
+    pub total: u64,
+    pub phase: DownloadPhase,
+    /// Human-readable description of the current phase
+    pub status: String,
+    /// Increases with every event, so the UI can drop updates that arrive
+    /// after a newer one for the same item
+    pub seq: u64,
+}
+
+static PROGRESS_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// Sequence number for the next `download-progress` event
+pub fn next_seq() -> u64 {
+    PROGRESS_SEQ.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+/// Error returned when a download is cancelled through [`ActiveDownloads`]
+pub const CANCELLED_ERROR: &str = "Download cancelled";
+
+/// A failed download, noting whether the server couldn't be reached at all
+/// (connection, timeout or request failure) as opposed to answering with an
+/// error or a bad file
+#[derive(Debug)]
+pub struct DownloadError {
+    pub message: String,
+    pub unreachable: bool,
+}
+
+impl From<String> for DownloadError {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            unreachable: false,
+        }
+    }
+}
+
+impl From<DownloadError> for String {
+    fn from(error: DownloadError) -> Self {
+        error.message
+    }
+}
+
+/// Downloads currently in flight, keyed by item name
+#[derive(Default)]
+pub struct ActiveDownloads(Mutex<HashMap<String, ActiveDownload>>);
+
+struct ActiveDownload {
+    /// Tells this registration apart from a later one of the same item
+    id: u64,
+    token: CancellationToken,
+    /// Set only while the file itself is being fetched
+    tmp_path: Option<PathBuf>,
+}
+
+static NEXT_DOWNLOAD_ID: AtomicU64 = AtomicU64::new(0);
+
+/// An item's registration in [`ActiveDownloads`], from [`ActiveDownloads::begin`].
+/// The item stays registered, and so can't be downloaded a second time, until
+/// this is dropped.
+pub struct DownloadGuard {
+    window: Window,
+    item: String,
+    id: u64,
+    token: CancellationToken,
+}
+
+impl DownloadGuard {
+    /// Cancelled by `cancel_download` for this item
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for DownloadGuard {
+    fn drop(&mut self) {
+        let downloads = self.window.state::<ActiveDownloads>();
+        let mut active = downloads.0.lock().unwrap_or_else(|p| p.into_inner());
+        if active.get(&self.item).is_some_and(|d| d.id == self.id) {
+            active.remove(&self.item);
+        }
+    }
+}
+
+impl ActiveDownloads {
+    /// Register a download of `item` for the whole command: lookup, fetch,
+    /// extraction and recording. Only one download of an item can run at a
+    /// time, since they share files and are cancelled by item name.
+    pub fn begin(&self, window: &Window, item: &str) -> Result<DownloadGuard, String> {
+        let mut active = self.0.lock().unwrap_or_else(|p| p.into_inner());
+        if active.contains_key(item) {
+            return Err(format!("'{}' is already downloading", item));
+        }
+        let id = NEXT_DOWNLOAD_ID.fetch_add(1, Ordering::Relaxed);
+        let token = CancellationToken::new();
+        active.insert(
+            item.to_string(),
+            ActiveDownload {
+                id,
+                token: token.clone(),
+                tmp_path: None,
+            },
+        );
+        Ok(DownloadGuard {
+            window: window.clone(),
+            item: item.to_string(),
+            id,
+            token,
+        })
+    }
+
+    /// Record the `.part` file a registered item is being fetched to, or
+    /// clear it with `None` once the fetch is over
+    fn set_tmp_path(&self, item: &str, tmp_path: Option<&Path>) {
+        let mut active = self.0.lock().unwrap_or_else(|p| p.into_inner());
+        if let Some(download) = active.get_mut(item) {
+            download.tmp_path = tmp_path.map(Path::to_path_buf);
+        }
+    }
+
+    /// Whether an in-flight download is writing to `tmp_path`
+    pub fn is_writing(&self, tmp_path: &Path) -> bool {
+        let active = self.0.lock().unwrap_or_else(|p| p.into_inner());
+        active
+            .values()
+            .any(|download| download.tmp_path.as_deref() == Some(tmp_path))
+    }
+
+    /// Cancel a single download. Returns false if it wasn't running.
+    pub fn cancel(&self, item: &str) -> bool {
+        let active = self.0.lock().unwrap_or_else(|p| p.into_inner());
+        match active.get(item) {
+            Some(download) => {
+                download.token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Cancel every download and remove their temp files.
+    /// Returns the items that were cancelled.
+    pub fn cancel_all(&self) -> Vec<String> {
+        let mut active = self.0.lock().unwrap_or_else(|p| p.into_inner());
+        active
+            .drain()
+            .map(|(item, download)| {
+                download.token.cancel();
+                // The task also cleans up once it observes the cancellation, but
+                // on exit it may never get the chance
+                if let Some(tmp_path) = &download.tmp_path {
+                    let _ = fs::remove_file(tmp_path);
+                }
+                item
+            })
+            .collect()
+    }
+}
+
+#[tauri::command]
+pub fn cancel_download(item: String, downloads: tauri::State<'_, ActiveDownloads>) -> bool {
+    downloads.cancel(&item)
+}
+
+#[tauri::command]
+pub fn cancel_all_downloads(downloads: tauri::State<'_, ActiveDownloads>) -> Vec<String> {
+    downloads.cancel_all()
+}
+
+/// Trace files being written by [`download_with_trace`], keyed by item name
+#[derive(Default)]
+pub struct DownloadTraces(Mutex<HashMap<String, DownloadTrace>>);
+
+struct DownloadTrace {
+    file: BufWriter<fs::File>,
+    started: Instant,
+}
+
+impl DownloadTraces {
+    fn start(&self, item: &str, path: &Path) -> Result<(), String> {
+        let mut traces = self.0.lock().unwrap_or_else(|p| p.into_inner());
+        // Checked first, so a rejected trace doesn't truncate the running one's file
+        if traces.contains_key(item) {
+            return Err(format!("'{}' is already being traced", item));
+        }
+        let file = fs::File::create(path)
+            .map_err(|e| errors::io_error("Failed to create trace file", &e))?;
+        traces.insert(
+            item.to_string(),
+            DownloadTrace {
+                file: BufWriter::new(file),
+                started: Instant::now(),
+            },
+        );
+        Ok(())
+    }
+
+    fn finish(&self, item: &str) {
+        let mut traces = self.0.lock().unwrap_or_else(|p| p.into_inner());
+        if let Some(mut trace) = traces.remove(item) {
+            let _ = trace.file.flush();
+        }
+    }
+
+    /// Append a line to the trace of `item`, if it is being traced. Lines are
+    /// prefixed with the wall-clock time and milliseconds since the start.
+    pub fn write(&self, item: &str, line: &str) {
+        let mut traces = self.0.lock().unwrap_or_else(|p| p.into_inner());
+        if let Some(trace) = traces.get_mut(item) {
+            let elapsed = trace.started.elapsed().as_millis();
+            let _ = writeln!(
+                trace.file,
+                "{} +{}ms {}",
+                chrono::Local::now().format("%H:%M:%S%.3f"),
+                elapsed,
+                line
+            );
+        }
+    }
+}
+
+/// Run the normal download of `item` ("flash_player", "ruffle" or a game id)
+/// while writing a detailed trace of it to `trace_path`: the request, response
+/// headers, every chunk received and the outcome. Returns the installed path.
+#[tauri::command]
+pub async fn download_with_trace(
+    window: Window,
+    item: String,
+    trace_path: String,
+    config: tauri::State<'_, AppConfig>,
+    traces: tauri::State<'_, DownloadTraces>,
+) -> Result<String, String> {
+    queue::check_item(&item, &config)?;
+    traces.start(&item, Path::new(&trace_path))?;
+    traces.write(
+        &item,
+        &format!("Trace of {} (launcher {})", item, env!("CARGO_PKG_VERSION")),
+    );
+
+    let result = queue::download_item(window, &item).await;
+    match &result {
+        Ok(path) => traces.write(&item, &format!("Finished: installed to {}", path)),
+        Err(e) => traces.write(&item, &format!("Failed: {}", e)),
+    }
+    traces.finish(&item);
+    result
+}
+
+/// Add downloaded bytes to the persistent data usage counters
+fn record_data_usage(window: &Window, item_name: &str, bytes: u64) {
+    if bytes == 0 {
+        return;
+    }
+    let result = config::update_versions(&window.state::<Mutex<GameVersions>>(), |versions| {
+        *versions
+            .data_usage
+            .entry(item_name.to_string())
+            .or_insert(0) += bytes;
+        versions
+            .data_usage_since
+            .get_or_insert_with(|| chrono::Utc::now().timestamp());
+    });
+    if let Err(e) = result {
+        logger::log(&format!("Failed to record data usage: {}", e));
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct DataUsage {
+    pub total_bytes: u64,
+    /// Bytes by item ("flash_player", "ruffle" or a game id)
+    pub items: std::collections::BTreeMap<String, u64>,
+    /// When counting started (seconds since the Unix epoch); `None` if
+    /// nothing has been downloaded since the last reset
+    pub since: Option<i64>,
+}
+
+/// Bytes the launcher has downloaded since counting started or was last reset,
+/// including failed and cancelled downloads
+#[tauri::command]
+pub fn get_data_usage(versions: tauri::State<'_, Mutex<GameVersions>>) -> DataUsage {
+    let versions = config::lock_versions(&versions);
+    DataUsage {
+        total_bytes: versions.data_usage.values().sum(),
+        items: versions
+            .data_usage
+            .iter()
+            .map(|(item, bytes)| (item.clone(), *bytes))
+            .collect(),
+        since: versions.data_usage_since,
+    }
+}
+
+/// Clear the data usage counters
+#[tauri::command]
+pub fn reset_data_usage(versions: tauri::State<'_, Mutex<GameVersions>>) -> Result<(), String> {
+    config::update_versions(&versions, |versions| {
+        versions.data_usage.clear();
+        versions.data_usage_since = None;
+    })
+}
+
+/// Start an HTTP client builder, pinning server certificates when the
+/// `pinned_cert_sha256` setting is non-empty
+pub fn client_builder(settings: &Mutex<Settings>) -> Result<reqwest::ClientBuilder, String> {
+    let pins = config::lock_settings(settings).pinned_cert_sha256.clone();
+
+    let builder = reqwest::Client::builder();
+    match pins {
+        Some(pins) if !pins.is_empty() => {
+            Ok(builder.tls_backend_preconfigured(tls::pinned_tls_config(&pins)?))
+        }
+        _ => Ok(builder),
+    }
+}
+
+/// Delays between checks in [`wait_for_install`]
+const INSTALL_CHECK_BACKOFF: [Duration; 5] = [
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(200),
+    Duration::from_millis(400),
+    Duration::from_millis(800),
+];
+
+/// Wait until an installed file is visible, retrying with backoff. Some
+/// Windows and network filesystems report a freshly renamed file as missing
+/// for a moment, so an install is only reported complete once it can be seen.
+pub async fn wait_for_install(path: &Path) -> Result<(), String> {
+    for delay in INSTALL_CHECK_BACKOFF {
+        if path.exists() {
+            return Ok(());
+        }
+        tokio::time::sleep(delay).await;
+    }
+    if path.exists() {
+        Ok(())
+    } else {
+        Err(format!(
+            "Installed file not found at {} after download",
+            path.display()
+        ))
+    }
+}
+
+/// Default download size limit, to avoid disk exhaustion from a runaway or
+/// misconfigured download. Overridden by the `max_download_bytes` setting.
+const MAX_DOWNLOAD_SIZE: u64 = 500 * 1024 * 1024; // 500 MB
+
+/// The download size limit from settings; `None` means unlimited
+fn max_download_size(settings: &Settings) -> Option<u64> {
+    match settings.max_download_bytes {
+        None => Some(MAX_DOWNLOAD_SIZE),
+        Some(0) => None,
+        Some(limit) => Some(limit),
+    }
+}
+
+/// Minimum time between `download-progress` events while downloading
+const PROGRESS_EMIT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Status reported while the total size is unknown (`total: 0`), which the UI
+/// shows as an indeterminate progress bar
+pub const STATUS_SIZE_UNKNOWN: &str = "Downloading (size unknown)...";
+
+/// Validators a server sent with a downloaded file
+#[derive(Debug, Clone, Default)]
+pub struct RemoteFile {
+    /// `Last-Modified` header, as sent
+    pub last_modified: Option<String>,
+    /// `ETag` header, as sent
+    pub etag: Option<String>,
+}
+
+/// Stream `url` to `dest`, emitting `download-progress` events for `item_name`.
+///
+/// Credentials embedded in the URL are sent as basic auth by reqwest; any
+/// `headers` are added to the request. When the server omits Content-Length
+/// (chunked transfer), `expected_size` is used for the percentage instead.
+/// The body is written to a `.part` file and renamed into place on success.
+/// `download` is the item's registration (see [`ActiveDownloads::begin`]),
+/// through which it can be cancelled. Returns the server's validators for the
+/// file.
+pub async fn download_file_with_progress(
+    download: &DownloadGuard,
+    url: &str,
+    headers: Option<&HashMap<String, String>>,
+    dest: &Path,
+    expected_size: Option<u64>,
+) -> Result<RemoteFile, String> {
+    download_to(download, url, headers, dest, expected_size, false)
+        .await
+        .map_err(String::from)
+}
+
+/// Like [`download_file_with_progress`], but a failed download keeps its
+/// `.part` file so the next attempt can continue it. The partial is only
+/// continued if the server still has the version it was started against,
+/// so two versions of a file that changes in place are never stitched
+/// together. The error tells whether the server was reachable.
+pub async fn download_resumable(
+    download: &DownloadGuard,
+    url: &str,
+    headers: Option<&HashMap<String, String>>,
+    dest: &Path,
+    expected_size: Option<u64>,
+) -> Result<RemoteFile, DownloadError> {
+    download_to(download, url, headers, dest, expected_size, true).await
+}
+
+/// Sidecar of a `.part` file recording what the partial download is of
+#[derive(serde::Serialize, serde::Deserialize)]
+struct PartMeta {
+    /// Redacted URL the partial was downloaded from
+    url: String,
+    /// ETag or Last-Modified the server sent when the download started, sent
+    /// back as `If-Range` when resuming
+    validator: String,
+}
+
+fn part_meta_path(tmp_path: &Path) -> PathBuf {
+    tmp_path.with_extension("part.meta")
+}
+
+fn discard_partial(tmp_path: &Path) {
+    let _ = fs::remove_file(tmp_path);
+    let _ = fs::remove_file(part_meta_path(tmp_path));
+}
+
+/// Offset and validator to resume a partial download of `url` from, if there
+/// is one
+fn resume_point(tmp_path: &Path, url: &str) -> Option<(u64, String)> {
+    let meta: PartMeta = serde_json::from_slice(&fs::read(part_meta_path(tmp_path)).ok()?).ok()?;
+    if meta.url != config::redact_url(url) {
+        return None;
+    }
+    let offset = fs::metadata(tmp_path).ok()?.len();
+    (offset > 0).then_some((offset, meta.validator))
+}
+
+async fn download_to(
+    download: &DownloadGuard,
+    url: &str,
+    headers: Option<&HashMap<String, String>>,
+    dest: &Path,
+    expected_size: Option<u64>,
+    resumable: bool,
+) -> Result<RemoteFile, DownloadError> {
+    let window = &download.window;
+    let item_name = download.item.as_str();
+    let downloads = window.state::<ActiveDownloads>();
+    let tmp_path = dest.with_extension("part");
+    downloads.set_tmp_path(item_name, Some(&tmp_path));
+
+    // Counted outside `fetch_to_file` so cancelled downloads still count
+    let received = AtomicU64::new(0);
+    let result = tokio::select! {
+        result = fetch_to_file(window, url, headers, dest, item_name, expected_size, &received) => result,
+        _ = download.token.cancelled() => Err(CANCELLED_ERROR.to_string().into()),
+    };
+
+    downloads.set_tmp_path(item_name, None);
+    record_data_usage(window, item_name, received.load(Ordering::Relaxed));
+    if let Err(DownloadError { message: e, .. }) = &result {
+        if resumable && e != CANCELLED_ERROR && tmp_path.exists() {
+            logger::log(&format!(
+                "Keeping partial download of {} to resume: {}",
+                item_name, e
+            ));
+        } else {
+            discard_partial(&tmp_path);
+        }
+        let phase = if e == CANCELLED_ERROR {
+            DownloadPhase::Cancelled
+        } else {
+            DownloadPhase::Failed
+        };
+        let _ = window.emit(
+            "download-progress",
+            DownloadProgress {
+                item: item_name.to_string(),
+                progress: 0,
+                downloaded: 0,
+                total: 0,
+                phase,
+                status: e.clone(),
+                seq: next_seq(),
+            },
+        );
+    }
+    result
+}
+
+/// Record what a new `.part` file is of, so it can be resumed. Without a
+/// strong validator from the server it can't be resumed safely, so any
+/// earlier record is removed instead.
+fn write_part_meta(tmp_path: &Path, url: &str, remote: &RemoteFile) {
+    let meta_path = part_meta_path(tmp_path);
+    // If-Range only accepts strong ETags
+    let validator = remote
+        .etag
+        .clone()
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| remote.last_modified.clone());
+    let Some(validator) = validator else {
+        let _ = fs::remove_file(&meta_path);
+        return;
+    };
+    let meta = PartMeta {
+        url: config::redact_url(url),
+        validator,
+    };
+    if let Ok(json) = serde_json::to_vec(&meta) {
+        let _ = fs::write(&meta_path, json);
+    }
+}
+
+async fn fetch_to_file(
+    window: &Window,
+    url: &str,
+    headers: Option<&HashMap<String, String>>,
+    dest: &Path,
+    item_name: &str,
+    expected_size: Option<u64>,
+    received: &AtomicU64,
+) -> Result<RemoteFile, DownloadError> {
+    let settings = window.state::<Mutex<Settings>>();
+    let (log_headers, max_size) = {
+        let settings = config::lock_settings(&settings);
+        (
+            settings.log_response_headers.unwrap_or(false),
+            max_download_size(&settings),
+        )
+    };
+    let client = client_builder(&settings)?
+        .timeout(Duration::from_secs(300))
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+    let traces = window.state::<DownloadTraces>();
+    traces.write(item_name, &format!("GET {}", config::redact_url(url)));
+
+    // Fail fast on a host that keeps failing, so fallbacks are tried sooner
+    let breakers = window.state::<HostBreakers>();
+    breakers.check(url).map_err(|e| {
+        traces.write(item_name, &e);
+        DownloadError {
+            message: e,
+            unreachable: true,
+        }
+    })?;
+
+    let mut request = client.get(url);
+    for (name, value) in headers.into_iter().flatten() {
+        // Header values may hold tokens, so only their names are traced
+        traces.write(item_name, &format!("Request header: {}", name));
+        request = request.header(name, value);
+    }
+
+    // With If-Range the server only sends the rest of the file if it is
+    // still the version the partial was started against; otherwise it sends
+    // the whole file
+    let tmp_path = dest.with_extension("part");
+    let resume = resume_point(&tmp_path, url);
+    if let Some((offset, validator)) = &resume {
+        traces.write(item_name, &format!("Resuming from byte {}", offset));
+        request = request
+            .header(reqwest::header::RANGE, format!("bytes={}-", offset))
+            .header(reqwest::header::IF_RANGE, validator);
+    }
+
+    let response = request.send().await.map_err(|e| {
+        breakers.record_failure(url);
+        let unreachable = e.is_connect() || e.is_timeout() || e.is_request();
+        let error = format!(
+            "Request to {} failed: {}",
+            config::redact_url(url),
+            e.without_url()
+        );
+        traces.write(item_name, &error);
+        DownloadError {
+            message: error,
+            unreachable,
+        }
+    })?;
+    if response.status().is_server_error() {
+        breakers.record_failure(url);
+    } else {
+        breakers.record_success(url);
+    }
+
+    if log_headers {
+        log_response(item_name, url, &response);
+    }
+    trace_response(&traces, item_name, &response);
+
+    if response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        // The partial doesn't fit the file on the server; start over next time
+        discard_partial(&tmp_path);
+    }
+    if !response.status().is_success() {
+        return Err(format!(
+            "HTTP error: {} from {}",
+            response.status(),
+            config::redact_url(url)
+        )
+        .into());
+    }
+
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string())
+    };
+    let remote = RemoteFile {
+        last_modified: header(reqwest::header::LAST_MODIFIED),
+        etag: header(reqwest::header::ETAG),
+    };
+
+    let resumed_from = match &resume {
+        Some((offset, _)) if response.status() == reqwest::StatusCode::PARTIAL_CONTENT => *offset,
+        Some(_) => {
+            logger::log(&format!(
+                "{} changed on the server, restarting its download",
+                item_name
+            ));
+            0
+        }
+        None => 0,
+    };
+
+    // For a resumed download this is the length of the rest of the file
+    let content_length = response.content_length();
+    if let (Some(length), Some(max)) = (content_length, max_size) {
+        if length + resumed_from > max {
+            return Err(format!(
+                "Remote file too large: {} bytes (limit {} bytes)",
+                length + resumed_from,
+                max
+            )
+            .into());
+        }
+    }
+
+    if let (Some(length), Some(parent)) = (content_length, dest.parent()) {
+        disk::ensure_space(parent, length)?;
+    }
+
+    // reqwest is built without transparent decompression, so Content-Length and
+    // `downloaded` both count the bytes exactly as they are written to disk
+    let total = content_length
+        .map(|length| length + resumed_from)
+        .or(expected_size)
+        .unwrap_or(0);
+    let status = if total > 0 {
+        "Downloading..."
+    } else {
+        STATUS_SIZE_UNKNOWN
+    };
+
+    let mut downloaded: u64 = resumed_from;
+
+    // The data directory may have been removed while the app was running
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| errors::io_error("Failed to create directory", &e))?;
+    }
+
+    // Write to a temporary file first, then atomically rename into place
+    let mut file = if resumed_from > 0 {
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&tmp_path)
+            .map_err(|e| errors::io_error("Failed to open partial download", &e))?
+    } else {
+        let file = fs::File::create(&tmp_path)
+            .map_err(|e| errors::io_error("Failed to create temp file", &e))?;
+        write_part_meta(&tmp_path, url, &remote);
+        file
+    };
+
+    let mut stream = response.bytes_stream();
+    use futures_util::StreamExt;
+
+    let mut last_emit: Option<Instant> = None;
+    let mut last_progress: Option<u32> = None;
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| {
+            breakers.record_failure(url);
+            let error = format!("Download error: {}", e.without_url());
+            traces.write(item_name, &format!("{} after {} bytes", error, downloaded));
+            error
+        })?;
+        downloaded += chunk.len() as u64;
+        received.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+        traces.write(
+            item_name,
+            &format!("Chunk: {} bytes, {} total", chunk.len(), downloaded),
+        );
+
+        if max_size.is_some_and(|max| downloaded > max) {
+            return Err("Download exceeded maximum allowed size".to_string().into());
+        }
+
+        file.write_all(&chunk)
+            .map_err(|e| errors::io_error("Write error", &e))?;
+
+        // An expected size is only an estimate, so never report past 100%
+        let progress = if total > 0 {
+            ((downloaded as f64 / total as f64) * 100.0).min(100.0) as u32
+        } else {
+            0
+        };
+
+        // Chunks can arrive thousands of times a second, so only emit once the
+        // interval has passed and the percentage has actually moved
+        let due = last_emit.is_none_or(|t| t.elapsed() >= PROGRESS_EMIT_INTERVAL);
+        let changed = total == 0 || last_progress != Some(progress);
+        if due && changed {
+            emit_downloading(window, item_name, progress, downloaded, total, status);
+            last_emit = Some(Instant::now());
+            last_progress = Some(progress);
+        }
+    }
+
+    traces.write(
+        item_name,
+        &format!("Stream ended: {} bytes received", downloaded),
+    );
+
+    // A connection dropped near the end just ends the stream, so check the
+    // length against Content-Length (not the expected size, which is an estimate)
+    if let Some(length) = content_length {
+        if downloaded != length + resumed_from {
+            return Err(format!(
+                "Download incomplete: received {} of {} bytes",
+                downloaded,
+                length + resumed_from
+            )
+            .into());
+        }
+    }
+
+    // Flush and rename
+    file.flush()
+        .map_err(|e| errors::io_error("Failed to flush file", &e))?;
+    fs::rename(&tmp_path, dest).map_err(|e| errors::io_error("Failed to rename temp file", &e))?;
+    let _ = fs::remove_file(part_meta_path(&tmp_path));
+
+    // The throttle may have skipped the last chunks
+    emit_downloading(window, item_name, 100, downloaded, total, status);
+
+    Ok(remote)
+}
+
+/// How long the post-download command may run before it is killed
+const POST_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Start the `post_download_command` hook, if one is set, with the item name
+/// and installed path. It runs in the background and its exit status is only
+/// logged, so a failing hook never fails the download. It is detached, so
+/// quitting the launcher doesn't stop it; while the launcher runs it is killed
+/// after [`POST_DOWNLOAD_TIMEOUT`].
+pub fn run_post_download_hook(settings: &Mutex<Settings>, item_name: &str, path: &Path) {
+    let command = config::lock_settings(settings)
+        .post_download_command
+        .clone()
+        .filter(|c| !c.trim().is_empty());
+    let Some(command) = command else {
+        return;
+    };
+
+    let mut cmd = tokio::process::Command::new(&command);
+    cmd.arg(item_name)
+        .arg(path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    let item_name = item_name.to_string();
+
+    tauri::async_runtime::spawn(async move {
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                logger::log(&format!(
+                    "Failed to start post-download command {} for {}: {}",
+                    command, item_name, e
+                ));
+                return;
+            }
+        };
+        match tokio::time::timeout(POST_DOWNLOAD_TIMEOUT, child.wait()).await {
+            Ok(Ok(status)) => logger::log(&format!(
+                "Post-download command for {} exited with {}",
+                item_name, status
+            )),
+            Ok(Err(e)) => logger::log(&format!(
+                "Failed to wait for post-download command for {}: {}",
+                item_name, e
+            )),
+            Err(_) => {
+                let _ = child.kill().await;
+                logger::log(&format!(
+                    "Post-download command for {} timed out after {}s and was killed",
+                    item_name,
+                    POST_DOWNLOAD_TIMEOUT.as_secs()
+                ));
+            }
+        }
+    });
+}
+
+/// Response headers worth logging when debugging a download
+const LOGGED_HEADERS: [reqwest::header::HeaderName; 6] = [
+    reqwest::header::CONTENT_TYPE,
+    reqwest::header::CONTENT_LENGTH,
+    reqwest::header::LAST_MODIFIED,
+    reqwest::header::ETAG,
+    reqwest::header::SERVER,
+    reqwest::header::CONTENT_ENCODING,
+];
+
+/// Write the status line and key headers of a download response to the log
+fn log_response(item_name: &str, url: &str, response: &reqwest::Response) {
+    let mut line = format!(
+        "Download {} from {}: {:?} {}",
+        item_name,
+        config::redact_url(url),
+        response.version(),
+        response.status()
+    );
+    for name in &LOGGED_HEADERS {
+        if let Some(value) = response.headers().get(name) {
+            line.push_str(&format!(
+                "; {}: {}",
+                name,
+                value.to_str().unwrap_or("<binary>")
+            ));
+        }
+    }
+    logger::log(&line);
+}
+
+/// Write the status line and every response header to the trace of
+/// `item_name`, leaving out cookies
+fn trace_response(traces: &DownloadTraces, item_name: &str, response: &reqwest::Response) {
+    traces.write(
+        item_name,
+        &format!("Response: {:?} {}", response.version(), response.status()),
+    );
+    for (name, value) in response.headers() {
+        let value = if name == reqwest::header::SET_COOKIE {
+            "[redacted]"
+        } else {
+            value.to_str().unwrap_or("<binary>")
+        };
+        traces.write(item_name, &format!("Response header: {}: {}", name, value));
+    }
+}
+
+/// Emit extraction progress for `item_name`
+pub fn emit_extracting(window: &Window, item_name: &str, progress: u32) {
+    let _ = window.emit(
+        "download-progress",
+        DownloadProgress {
+            item: item_name.to_string(),
+            progress,
+            downloaded: 0,
+            total: 0,
+            phase: DownloadPhase::Extracting,
+            status: "Extracting...".to_string(),
+            seq: next_seq(),
+        },
+    );
+}
+
+/// Report an install that failed after its download finished, e.g. while
+/// extracting or validating it
+pub fn emit_failed(window: &Window, item_name: &str, error: &str) {
+    let _ = window.emit(
+        "download-progress",
+        DownloadProgress {
+            item: item_name.to_string(),
+            progress: 0,
+            downloaded: 0,
+            total: 0,
+            phase: DownloadPhase::Failed,
+            status: error.to_string(),
+            seq: next_seq(),
+        },
+    );
+}
+
+fn emit_downloading(
+    window: &Window,
+    item_name: &str,
+    progress: u32,
+    downloaded: u64,
+    total: u64,
+    status: &str,
+) {
+    let _ = window.emit(
+        "download-progress",
+        DownloadProgress {
+            item: item_name.to_string(),
+            progress,
+            downloaded,
+            total,
+            phase: DownloadPhase::Downloading,
+            status: status.to_string(),
+            seq: next_seq(),
+        },
+    );
+}
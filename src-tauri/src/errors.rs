@@ -0,0 +1,107 @@
+//! User-facing messages for filesystem errors, with a hint at what to do
+//! about the common ones.
+
+use std::io;
+use std::path::Path;
+
+/// What the user can do about an error of this kind, if there's anything
+/// more useful to say than the OS message
+fn remediation(kind: io::ErrorKind) -> Option<&'static str> {
+    match kind {
+        io::ErrorKind::PermissionDenied => Some(
+            "The launcher isn't allowed to write there. Check the folder's permissions, \
+             close any program using the file, or choose a different install folder.",
+        ),
+        io::ErrorKind::NotFound => Some(
+            "A launcher folder may have been moved or deleted. Restart the launcher to \
+             recreate it, then try again.",
+        ),
+        io::ErrorKind::StorageFull => Some("The disk is full. Free up some space and try again."),
+        io::ErrorKind::ReadOnlyFilesystem => {
+            Some("The drive is read-only. Choose an install folder on a writable drive.")
+        }
+        _ => None,
+    }
+}
+
+/// Format an IO error as "`action`: error", followed by a remediation hint
+/// for permission, missing-folder and disk-full errors
+pub fn io_error(action: &str, e: &io::Error) -> String {
+    match remediation(e.kind()) {
+        Some(hint) => format!("{}: {}. {}", action, e, hint),
+        None => format!("{}: {}", action, e),
+    }
+}
+
+/// Format a failure to start `program` as "`action`: error". When the OS
+/// refused to run it, say why if we can tell: a missing execute bit, or a
+/// filesystem mounted `noexec`.
+pub fn spawn_error(action: &str, program: &Path, e: &io::Error) -> String {
+    if e.kind() != io::ErrorKind::PermissionDenied {
+        return format!("{}: {}", action, e);
+    }
+
+    let hint = if is_noexec_mount(program) {
+        format!(
+            "{} is on a filesystem mounted noexec, so programs can't run from it. \
+             Move the launcher's data folder to another drive, or remount it without noexec.",
+            program.display()
+        )
+    } else if !is_executable(program) {
+        format!(
+            "{} isn't marked executable. Download the player again to restore its \
+             permissions, or run `chmod +x` on it.",
+            program.display()
+        )
+    } else {
+        format!(
+            "The system refused to run {}. Check its permissions and any security \
+             policy (such as SELinux or AppArmor) that may block it.",
+            program.display()
+        )
+    };
+    format!("{}: {}. {}", action, e, hint)
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    // Bare program names are resolved through PATH; don't second-guess those
+    if path.components().count() < 2 {
+        return true;
+    }
+    std::fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
+
+/// Whether `path` lies on a mount with the `noexec` option, going by the
+/// longest matching mount point in /proc/self/mounts
+#[cfg(target_os = "linux")]
+fn is_noexec_mount(path: &Path) -> bool {
+    let Ok(path) = path.canonicalize() else {
+        return false;
+    };
+    let Ok(mounts) = std::fs::read_to_string("/proc/self/mounts") else {
+        return false;
+    };
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = fields.nth(1)?.replace("\\040", " ");
+            let options = fields.nth(1)?;
+            Some((mount_point, options.split(',').any(|o| o == "noexec")))
+        })
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.len())
+        .is_some_and(|(_, noexec)| noexec)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_noexec_mount(_path: &Path) -> bool {
+    false
+}
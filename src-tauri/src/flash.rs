@@ -1,30 +1,26 @@
-use crate::config::{self, AppConfig, Settings};
+use crate::archive;
+use crate::config::{self, AppConfig, GameVersions, Settings};
+use crate::download::{self, download_file_with_progress, DownloadPhase, DownloadProgress};
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+use crate::logger;
+use crate::{errors, integrity};
 use std::fs;
-use std::io::Write;
-use std::path::PathBuf;
-use std::time::Duration;
-use tauri::{Emitter, Window};
-
-#[derive(Clone, serde::Serialize)]
-pub struct DownloadProgress {
-    pub item: String,
-    pub progress: u32,
-    pub downloaded: u64,
-    pub total: u64,
-    pub status: String,
-}
-
+use std::path::Path;
+#[cfg(target_os = "macos")]
+use std::process::Command;
 use std::sync::Mutex;
+use tauri::{Emitter, Manager, Window};
+
+/// How long to wait before checking the downloaded projector wasn't quarantined
+#[cfg(target_os = "windows")]
+const QUARANTINE_CHECK_DELAY: std::time::Duration = std::time::Duration::from_secs(3);
 
 #[tauri::command]
 pub fn check_flash_installed(
     config: tauri::State<'_, AppConfig>,
     settings: tauri::State<'_, Mutex<Settings>>,
 ) -> bool {
-    let settings = match settings.lock() {
-        Ok(s) => s,
-        Err(p) => p.into_inner(),
-    };
+    let settings = config::lock_settings(&settings);
 
     match config::get_flash_player_path(&config, &settings) {
         Ok(path) => path.exists(),
@@ -37,10 +33,7 @@ pub fn get_flash_path(
     config: tauri::State<'_, AppConfig>,
     settings: tauri::State<'_, Mutex<Settings>>,
 ) -> Result<String, String> {
-    let settings = match settings.lock() {
-        Ok(s) => s,
-        Err(p) => p.into_inner(),
-    };
+    let settings = config::lock_settings(&settings);
 
     let path = config::get_flash_player_path(&config, &settings)?;
     path.to_str()
@@ -51,12 +44,40 @@ pub fn get_flash_path(
 #[tauri::command]
 pub async fn download_flash(
     window: Window,
+    install_dir: Option<String>,
     config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
 ) -> Result<String, String> {
+    // Held until the install is recorded, so a second download can't stage
+    // over this one
+    let download = window
+        .state::<download::ActiveDownloads>()
+        .begin(&window, "flash_player")?;
+
+    #[cfg(target_os = "macos")]
+    let symlink = config::lock_settings(&settings)
+        .macos_flash_symlink
+        .unwrap_or(false);
+
+    #[cfg(target_os = "linux")]
+    let use_wine = config::lock_settings(&settings)
+        .use_wine_flash
+        .unwrap_or(false);
+    // Fail before downloading a projector that can't be run
+    #[cfg(target_os = "linux")]
+    if use_wine {
+        find_wine()?;
+    }
+
     // Get download info based on OS
-    let flash_dir = config::get_flash_dir()?;
+    let custom_dir = config::custom_install_dir(install_dir.as_deref(), "Flash");
+    let flash_dir = match &custom_dir {
+        Some(dir) => dir.clone(),
+        None => config::get_flash_dir()?,
+    };
     fs::create_dir_all(&flash_dir)
-        .map_err(|e| format!("Failed to create flash directory: {}", e))?;
+        .map_err(|e| errors::io_error("Failed to create flash directory", &e))?;
 
     #[cfg(target_os = "windows")]
     let (primary_url, fallback_url, filename) = (
@@ -72,12 +93,21 @@ pub async fn download_flash(
         "flash_player.dmg",
     );
 
+    // With Wine the Windows projector is used, which needs no extraction
     #[cfg(target_os = "linux")]
-    let (primary_url, fallback_url, filename) = (
-        &config.flash_player.linux.primary_url,
-        &config.flash_player.linux.fallback_url,
-        "flash_player.tar.gz",
-    );
+    let (primary_url, fallback_url, filename) = if use_wine {
+        (
+            &config.flash_player.windows.primary_url,
+            &config.flash_player.windows.fallback_url,
+            config.flash_player.windows.filename.as_str(),
+        )
+    } else {
+        (
+            &config.flash_player.linux.primary_url,
+            &config.flash_player.linux.fallback_url,
+            "flash_player.tar.gz",
+        )
+    };
 
     let download_path = flash_dir.join(filename);
 
@@ -89,13 +119,18 @@ pub async fn download_flash(
             progress: 0,
             downloaded: 0,
             total: 0,
+            phase: DownloadPhase::Connecting,
             status: "Starting download...".to_string(),
+            seq: download::next_seq(),
         },
     );
 
+    let expected_size = config.expected_sizes.get("flash_player").copied();
+
     // Try primary URL first, then fallback if necessary
     let primary_attempt =
-        download_file_with_progress(&window, primary_url, &download_path, "flash_player").await;
+        download_file_with_progress(&download, primary_url, None, &download_path, expected_size)
+            .await;
     if primary_attempt.is_err() {
         if let Some(fallback) = fallback_url {
             let _ = window.emit(
@@ -105,10 +140,13 @@ pub async fn download_flash(
                     progress: 0,
                     downloaded: 0,
                     total: 0,
+                    phase: DownloadPhase::Connecting,
                     status: "Primary failed, trying fallback...".to_string(),
+                    seq: download::next_seq(),
                 },
             );
-            download_file_with_progress(&window, fallback, &download_path, "flash_player").await?;
+            download_file_with_progress(&download, fallback, None, &download_path, expected_size)
+                .await?;
         } else {
             return Err(primary_attempt
                 .err()
@@ -118,9 +156,36 @@ pub async fn download_flash(
 
     // Extract based on OS
     #[cfg(target_os = "linux")]
-    {
-        extract_tar_gz(&download_path, &flash_dir)?;
+    let extract = !use_wine;
+    #[cfg(target_os = "macos")]
+    let extract = true;
+
+    // The tar.gz extraction reports its own progress; a DMG copy doesn't
+    #[cfg(target_os = "linux")]
+    if extract {
+        download::emit_extracting(&window, "flash_player", 0);
+    }
+    #[cfg(target_os = "macos")]
+    if extract {
+        download::emit_extracting(&window, "flash_player", 100);
+    }
+
+    #[cfg(target_os = "linux")]
+    if extract {
+        // settings.json lives in the flash directory and must survive reinstalls
+        let installed = archive::stage_install(
+            &flash_dir,
+            &config.flash_player.linux.filename,
+            &["settings.json"],
+            |staging| {
+                archive::extract_tar_gz_with_progress(&download_path, staging, |progress| {
+                    download::emit_extracting(&window, "flash_player", progress)
+                })?;
+                normalize_flash_binary(staging, &config.flash_player.linux.filename)
+            },
+        );
         let _ = fs::remove_file(&download_path);
+        installed.inspect_err(|e| download::emit_failed(&window, "flash_player", e))?;
 
         // Make executable
         let flash_bin = flash_dir.join(&config.flash_player.linux.filename);
@@ -131,26 +196,92 @@ pub async fn download_flash(
                 .permissions();
             perms.set_mode(0o755);
             fs::set_permissions(&flash_bin, perms)
-                .map_err(|e| format!("Failed to set permissions: {}", e))?;
+                .map_err(|e| errors::io_error("Failed to set permissions", &e))?;
         }
     }
 
     #[cfg(target_os = "macos")]
     {
-        extract_dmg(
-            &download_path,
-            &flash_dir,
-            &config.flash_player.macos.filename,
-        )?;
+        let app_name = &config.flash_player.macos.filename;
+        let installed =
+            archive::stage_install(&flash_dir, app_name, &["settings.json"], |staging| {
+                extract_dmg(&download_path, staging, app_name, symlink)
+            });
         let _ = fs::remove_file(&download_path);
+        installed.inspect_err(|e| download::emit_failed(&window, "flash_player", e))?;
+
+        // A symlinked app lives on the read-only DMG volume, which is never
+        // quarantined
+        let app_path = flash_dir.join(app_name);
+        let is_symlink = fs::symlink_metadata(&app_path)
+            .map(|m| m.file_type().is_symlink())
+            .unwrap_or(false);
+        if !is_symlink {
+            archive::clear_quarantine(&app_path)
+                .inspect_err(|e| download::emit_failed(&window, "flash_player", e))?;
+        }
+    }
+
+    // Antivirus software sometimes quarantines the projector shortly after it's
+    // written, which otherwise shows up later as Flash mysteriously uninstalling
+    #[cfg(target_os = "windows")]
+    {
+        tokio::time::sleep(QUARANTINE_CHECK_DELAY).await;
+        if !download_path.exists() {
+            logger::log(&format!(
+                "Flash Player disappeared after download, likely quarantined by antivirus: {}",
+                download_path.display()
+            ));
+            let error = format!(
+                "Flash Player was removed right after downloading, most likely by antivirus \
+                 quarantine. Add an exclusion for {} in your antivirus and download again.",
+                flash_dir.display()
+            );
+            download::emit_failed(&window, "flash_player", &error);
+            return Err(error);
+        }
     }
 
     // Update version info
-    let mut versions = config::load_versions().unwrap_or_default();
-    versions.flash_player = config.flash_player.fallback_version.clone();
-    config::save_versions(&versions)?;
+    config::update_versions(&versions, |versions| {
+        versions.flash_player = config.flash_player.fallback_version.clone();
+    })?;
 
-    // Emit completion
+    #[cfg(target_os = "windows")]
+    let final_path = flash_dir.join(&config.flash_player.windows.filename);
+    #[cfg(target_os = "macos")]
+    let final_path = flash_dir.join(&config.flash_player.macos.filename);
+    #[cfg(target_os = "linux")]
+    let final_path = if use_wine {
+        flash_dir.join(&config.flash_player.windows.filename)
+    } else {
+        flash_dir.join(&config.flash_player.linux.filename)
+    };
+
+    download::wait_for_install(&final_path).await?;
+
+    let hashes = integrity::hash_tree_async(final_path.clone()).await?;
+    config::update_versions(&versions, |versions| integrity::record(versions, hashes))?;
+
+    let final_path = final_path
+        .to_str()
+        .map(|s| s.to_string())
+        .ok_or_else(|| "Invalid path".to_string())?;
+
+    // Point settings at the custom install so launches find it
+    if custom_dir.is_some() {
+        let updated = {
+            let mut settings = config::lock_settings(&settings);
+            settings.flash_player_path = Some(final_path.clone());
+            settings.clone()
+        };
+        config::save_settings(&updated)?;
+    }
+
+    download::run_post_download_hook(&settings, "flash_player", std::path::Path::new(&final_path));
+
+    // Emit completion only once the install is visible and settings point at
+    // it, so a `check_flash_installed` triggered by this event sees it
     let _ = window.emit(
         "download-progress",
         DownloadProgress {
@@ -158,129 +289,253 @@ pub async fn download_flash(
             progress: 100,
             downloaded: 0,
             total: 0,
+            phase: DownloadPhase::Complete,
             status: "Download complete".to_string(),
+            seq: download::next_seq(),
         },
     );
 
+    Ok(final_path)
+}
+
+/// Primary URL `download_flash` downloads from on this platform
+pub fn download_url<'a>(config: &'a AppConfig, settings: &Settings) -> &'a str {
     #[cfg(target_os = "windows")]
-    let final_path = flash_dir.join(&config.flash_player.windows.filename);
+    let url = &config.flash_player.windows.primary_url;
     #[cfg(target_os = "macos")]
-    let final_path = flash_dir.join(&config.flash_player.macos.filename);
+    let url = &config.flash_player.macos.primary_url;
     #[cfg(target_os = "linux")]
-    let final_path = flash_dir.join(&config.flash_player.linux.filename);
+    let url = if settings.use_wine_flash.unwrap_or(false) {
+        &config.flash_player.windows.primary_url
+    } else {
+        &config.flash_player.linux.primary_url
+    };
+    #[cfg(not(target_os = "linux"))]
+    let _ = settings;
 
-    final_path
-        .to_str()
-        .map(|s| s.to_string())
-        .ok_or_else(|| "Invalid path".to_string())
+    url
 }
 
-async fn download_file_with_progress(
-    window: &Window,
-    url: &str,
-    dest: &PathBuf,
-    item_name: &str,
-) -> Result<(), String> {
-    // Limit downloads to a reasonable maximum to avoid disk exhaustion
-    const MAX_DOWNLOAD_SIZE: u64 = 500 * 1024 * 1024; // 500 MB
-
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(300))
-        .build()
-        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
-
-    let response = client
-        .get(url)
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!("HTTP error: {}", response.status()));
+/// Check a custom Flash Player path without running it: it must exist, be
+/// executable and be built for this platform (an .app bundle on macOS, the
+/// Windows projector when Linux uses Wine). Use `test_player` to start it.
+// `settings` only matters for Wine on Linux
+#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+pub fn check_flash_path(path: &Path, settings: &Settings) -> Result<(), String> {
+    if !path.exists() {
+        return Err("Path does not exist".to_string());
     }
 
-    let total = response.content_length().unwrap_or(0);
-    if total > MAX_DOWNLOAD_SIZE {
-        return Err(format!("Remote file too large: {} bytes", total));
+    #[cfg(target_os = "macos")]
+    if path.is_dir() {
+        return check_app_bundle(path);
     }
 
-    let mut downloaded: u64 = 0;
+    #[cfg(target_os = "linux")]
+    if settings.use_wine_flash.unwrap_or(false) {
+        return if path.is_file() {
+            Ok(())
+        } else {
+            Err("Not a file".to_string())
+        };
+    }
 
-    // Write to a temporary file first, then atomically rename into place
-    let tmp_path = dest.with_extension("part");
-    let mut file =
-        fs::File::create(&tmp_path).map_err(|e| format!("Failed to create temp file: {}", e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let metadata = fs::metadata(path).map_err(|e| errors::io_error("Failed to read", &e))?;
+        if metadata.permissions().mode() & 0o111 == 0 {
+            return Err("Not executable".to_string());
+        }
+    }
+    archive::check_binary_platform(path)
+}
 
-    let mut stream = response.bytes_stream();
-    use futures_util::StreamExt;
+/// Find `wine` on PATH, for running the Windows projector on Linux
+#[cfg(target_os = "linux")]
+pub fn find_wine() -> Result<std::path::PathBuf, String> {
+    std::env::var_os("PATH")
+        .and_then(|paths| {
+            std::env::split_paths(&paths)
+                .map(|dir| dir.join("wine"))
+                .find(|path| path.is_file())
+        })
+        .ok_or_else(|| {
+            "Wine was not found on PATH. Install Wine or turn off the Wine option for Flash."
+                .to_string()
+        })
+}
 
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk.map_err(|e| format!("Download error: {}", e))?;
-        downloaded += chunk.len() as u64;
+/// Find the projector in an extracted Linux tarball and move it to `filename`
+/// at the top level, since its name and location have varied between builds
+#[cfg(target_os = "linux")]
+fn normalize_flash_binary(dir: &Path, filename: &str) -> Result<(), String> {
+    let expected = dir.join(filename);
+    if is_elf_executable(&expected) {
+        return Ok(());
+    }
+
+    let candidates: Vec<_> = walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.into_path())
+        .filter(|path| is_elf_executable(path))
+        .collect();
+
+    let found = candidates
+        .iter()
+        .find(|path| path.file_name().is_some_and(|name| name == filename))
+        .or_else(|| {
+            candidates.iter().find(|path| {
+                path.file_name()
+                    .is_some_and(|name| name.to_string_lossy().to_lowercase().contains("flash"))
+            })
+        })
+        .or_else(|| candidates.first())
+        .ok_or_else(|| {
+            "No Flash Player executable was found in the downloaded archive".to_string()
+        })?;
+
+    fs::rename(found, &expected).map_err(|e| format!("Failed to move Flash Player binary: {}", e))
+}
 
-        if downloaded > MAX_DOWNLOAD_SIZE {
-            let _ = fs::remove_file(&tmp_path);
-            return Err("Download exceeded maximum allowed size".to_string());
+/// An ELF binary that isn't a shared library
+#[cfg(target_os = "linux")]
+fn is_elf_executable(path: &Path) -> bool {
+    use std::io::Read;
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if name.ends_with(".so") || name.contains(".so.") {
+        return false;
+    }
+
+    let mut magic = [0u8; 4];
+    fs::File::open(path)
+        .and_then(|mut f| f.read_exact(&mut magic))
+        .is_ok()
+        && magic == *b"\x7fELF"
+}
+
+/// Where a symlinked Flash Player DMG is kept, relative to the app directory
+#[cfg(target_os = "macos")]
+const FLASH_DMG_NAME: &str = "flash_player.dmg";
+
+/// Persistent mount point for a symlinked Flash Player DMG
+#[cfg(target_os = "macos")]
+const FLASH_VOLUME_DIR: &str = "FlashVolume";
+
+#[cfg(target_os = "macos")]
+fn extract_dmg(dmg_path: &Path, dest: &Path, app_name: &str, symlink: bool) -> Result<(), String> {
+    if symlink {
+        match link_dmg_app(dmg_path, dest, app_name) {
+            Ok(()) => return Ok(()),
+            Err(e) => logger::log(&format!(
+                "Warning: failed to symlink Flash Player, copying instead: {}",
+                e
+            )),
         }
+    }
 
-        file.write_all(&chunk)
-            .map_err(|e| format!("Write error: {}", e))?;
+    let mount_point = std::env::temp_dir().join("ptd_flash_mount");
+    attach_dmg(dmg_path, &mount_point)?;
 
-        let progress = if total > 0 {
-            ((downloaded as f64 / total as f64) * 100.0) as u32
-        } else {
-            0
-        };
+    // Copy app, once more if the first copy came out incomplete
+    let source = mount_point.join(app_name);
+    let dest_app = dest.join(app_name);
+    let copied = if source.exists() {
+        copy_app(&source, dest, &dest_app).or_else(|e| {
+            logger::log(&format!(
+                "Warning: Flash Player copy incomplete, retrying: {}",
+                e
+            ));
+            let _ = fs::remove_dir_all(&dest_app);
+            copy_app(&source, dest, &dest_app)
+        })
+    } else {
+        Err(format!("'{}' not found in DMG", app_name))
+    };
 
-        let _ = window.emit(
-            "download-progress",
-            DownloadProgress {
-                item: item_name.to_string(),
-                progress,
-                downloaded,
-                total,
-                status: "Downloading...".to_string(),
-            },
-        );
+    // Unmount DMG
+    let out_un = detach_command(&mount_point).output();
+
+    if let Ok(out_un) = out_un {
+        if !out_un.status.success() {
+            logger::log(&format!(
+                "Warning: failed to unmount DMG: {}",
+                String::from_utf8_lossy(&out_un.stderr)
+            ));
+        }
     }
 
-    // Flush and rename
-    file.flush()
-        .map_err(|e| format!("Failed to flush file: {}", e))?;
-    fs::rename(&tmp_path, dest).map_err(|e| format!("Failed to rename temp file: {}", e))?;
+    let _ = fs::remove_dir_all(&mount_point);
 
-    Ok(())
+    copied
 }
 
-#[cfg(target_os = "linux")]
-fn extract_tar_gz(archive: &PathBuf, dest: &PathBuf) -> Result<(), String> {
-    use flate2::read::GzDecoder;
-    use tar::Archive;
-
-    let file = fs::File::open(archive).map_err(|e| format!("Failed to open archive: {}", e))?;
-    let decoder = GzDecoder::new(file);
-    let mut archive = Archive::new(decoder);
-    archive
-        .unpack(dest)
-        .map_err(|e| format!("Failed to extract archive: {}", e))?;
-    Ok(())
+/// Copy an app bundle into `dest` and check the copy is complete
+#[cfg(target_os = "macos")]
+fn copy_app(source: &Path, dest: &Path, dest_app: &Path) -> Result<(), String> {
+    fs_extra::dir::copy(source, dest, &fs_extra::dir::CopyOptions::new())
+        .map_err(|e| format!("Failed to copy app: {}", e))?;
+    check_app_bundle(dest_app)
 }
 
+/// Check an app bundle has the executable its Info.plist names under
+/// `Contents/MacOS`, that it can be run and is a macOS binary
 #[cfg(target_os = "macos")]
-fn extract_dmg(dmg_path: &PathBuf, dest: &PathBuf, app_name: &str) -> Result<(), String> {
-    use std::process::Command;
+fn check_app_bundle(app: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let executable = bundle_executable(app)?;
+    let metadata = fs::metadata(&executable).map_err(|e| {
+        format!(
+            "{} is missing from the app bundle: {}",
+            executable.display(),
+            e
+        )
+    })?;
+    if metadata.len() == 0 {
+        return Err(format!("{} is empty", executable.display()));
+    }
+    if metadata.permissions().mode() & 0o111 == 0 {
+        return Err(format!("{} isn't executable", executable.display()));
+    }
+    archive::check_binary_platform(&executable)
+}
 
-    let mount_point = std::env::temp_dir().join("ptd_flash_mount");
-    fs::create_dir_all(&mount_point).map_err(|e| format!("Failed to create mount point: {}", e))?;
-
-    // Mount DMG
-    let out = Command::new("hdiutil")
-        .args([
-            "attach",
-            dmg_path.to_str().ok_or("Invalid dmg path")?,
-            "-mountpoint",
-            mount_point.to_str().ok_or("Invalid mount point")?,
-        ])
+/// The executable an app bundle runs: the one its Info.plist names under
+/// `Contents/MacOS`, or the first file there if the plist doesn't say
+#[cfg(target_os = "macos")]
+pub fn bundle_executable(app: &Path) -> Result<std::path::PathBuf, String> {
+    let macos_dir = app.join("Contents").join("MacOS");
+    match bundle_executable_name(app) {
+        Some(name) => Ok(macos_dir.join(name)),
+        None => fs::read_dir(&macos_dir)
+            .ok()
+            .and_then(|entries| entries.flatten().map(|e| e.path()).find(|p| p.is_file()))
+            .ok_or_else(|| format!("{} has no executable in Contents/MacOS", app.display())),
+    }
+}
+
+/// `CFBundleExecutable` from a bundle's XML Info.plist
+#[cfg(target_os = "macos")]
+fn bundle_executable_name(app: &Path) -> Option<String> {
+    let plist = fs::read_to_string(app.join("Contents").join("Info.plist")).ok()?;
+    let after_key = &plist[plist.find("<key>CFBundleExecutable</key>")?..];
+    let start = after_key.find("<string>")? + "<string>".len();
+    let end = after_key[start..].find("</string>")? + start;
+    Some(after_key[start..end].trim().to_string()).filter(|name| !name.is_empty())
+}
+
+#[cfg(target_os = "macos")]
+fn attach_dmg(dmg_path: &Path, mount_point: &Path) -> Result<(), String> {
+    fs::create_dir_all(mount_point).map_err(|e| format!("Failed to create mount point: {}", e))?;
+
+    let out = attach_command(dmg_path, mount_point)
         .output()
         .map_err(|e| format!("Failed to mount DMG: {}", e))?;
 
@@ -291,29 +546,103 @@ fn extract_dmg(dmg_path: &PathBuf, dest: &PathBuf, app_name: &str) -> Result<(),
         ));
     }
 
-    // Copy app
+    Ok(())
+}
+
+/// `hdiutil attach` for a DMG. Paths go in as separate arguments so spaces
+/// and non-ASCII characters in the user's home directory survive.
+#[cfg(target_os = "macos")]
+fn attach_command(dmg_path: &Path, mount_point: &Path) -> Command {
+    let mut cmd = Command::new("hdiutil");
+    cmd.arg("attach")
+        .arg(dmg_path)
+        .arg("-mountpoint")
+        .arg(mount_point);
+    cmd
+}
+
+/// `hdiutil detach` for a mount point
+#[cfg(target_os = "macos")]
+fn detach_command(mount_point: &Path) -> Command {
+    let mut cmd = Command::new("hdiutil");
+    cmd.arg("detach").arg(mount_point);
+    cmd
+}
+
+/// Mount the DMG at a persistent location and symlink the app out of it
+/// instead of copying the bundle. The DMG is kept in the app directory so the
+/// volume can be re-attached later (see [`remount_flash_volume`]).
+#[cfg(target_os = "macos")]
+fn link_dmg_app(dmg_path: &Path, dest: &Path, app_name: &str) -> Result<(), String> {
+    let app_dir = config::get_app_dir()?;
+    let mount_point = app_dir.join(FLASH_VOLUME_DIR);
+
+    // Release a volume left over from a previous install
+    let _ = detach_command(&mount_point).output();
+
+    attach_dmg(dmg_path, &mount_point)?;
+
     let source = mount_point.join(app_name);
-    let dest_app = dest.join(app_name);
-    if source.exists() {
-        fs_extra::dir::copy(&source, &dest, &fs_extra::dir::CopyOptions::new())
-            .map_err(|e| format!("Failed to copy app: {}", e))?;
+    let linked = if source.exists() {
+        std::os::unix::fs::symlink(&source, dest.join(app_name))
+            .map_err(|e| format!("Failed to create symlink: {}", e))
+    } else {
+        Err(format!("'{}' not found in DMG", app_name))
+    };
+
+    if let Err(e) = linked {
+        let _ = detach_command(&mount_point).output();
+        return Err(e);
     }
 
-    // Unmount DMG
-    let out_un = Command::new("hdiutil")
-        .args(["detach", mount_point.to_str().ok_or("Invalid mount point")?])
-        .output();
+    // An attached image can be moved, so keep it where remounting will find it
+    fs::rename(dmg_path, app_dir.join(FLASH_DMG_NAME))
+        .map_err(|e| format!("Failed to keep Flash Player DMG: {}", e))
+}
 
-    if let Ok(out_un) = out_un {
-        if !out_un.status.success() {
-            eprintln!(
-                "Warning: failed to unmount DMG: {}",
-                String::from_utf8_lossy(&out_un.stderr)
-            );
-        }
+/// Re-attach the persistent Flash Player DMG when `player_path` is a symlink
+/// into a volume that is no longer mounted (e.g. after a reboot)
+#[cfg(target_os = "macos")]
+pub fn remount_flash_volume(player_path: &Path) -> Result<(), String> {
+    let is_symlink = fs::symlink_metadata(player_path)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false);
+    if !is_symlink || player_path.exists() {
+        return Ok(());
     }
 
-    let _ = fs::remove_dir_all(&mount_point);
+    let app_dir = config::get_app_dir()?;
+    attach_dmg(
+        &app_dir.join(FLASH_DMG_NAME),
+        &app_dir.join(FLASH_VOLUME_DIR),
+    )
+}
 
-    Ok(())
+#[cfg(all(test, target_os = "macos"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hdiutil_commands_pass_paths_as_single_arguments() {
+        let root = Path::new("/Users/zoë doe/Library/Application Support/PTD Launcher");
+        let dmg = root.join("ü").join("flash player.dmg");
+        let mount = root.join("ü").join("Flash Volume");
+
+        let attach = attach_command(&dmg, &mount);
+        assert_eq!(
+            attach.get_args().collect::<Vec<_>>(),
+            [
+                "attach".as_ref(),
+                dmg.as_os_str(),
+                "-mountpoint".as_ref(),
+                mount.as_os_str()
+            ]
+        );
+
+        let detach = detach_command(&mount);
+        assert_eq!(
+            detach.get_args().collect::<Vec<_>>(),
+            ["detach".as_ref(), mount.as_os_str()]
+        );
+    }
 }
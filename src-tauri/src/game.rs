@@ -1,49 +1,585 @@
-use crate::config::{self, AppConfig, Settings};
+use crate::config::{self, AppConfig, GameEntry, GameVersion, GameVersions, Settings};
+use crate::download::{
+    self, download_file_with_progress, ActiveDownloads, DownloadPhase, DownloadProgress,
+};
+use crate::process::{LaunchId, RunningGames};
+use crate::{diagnostics, errors, integrity};
+use std::collections::HashMap;
+use std::ffi::OsString;
 use std::fs;
-use std::io::Write;
-use std::path::PathBuf;
-use std::process::Command;
-use tauri::{Emitter, Window};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{ChildStderr, Command, ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{Emitter, Manager, Window};
 
-use crate::flash::DownloadProgress;
-
-fn find_game_path(game_id: &str) -> Result<Option<PathBuf>, String> {
+/// Find the active build of a game. Games may be flat in the games directory
+/// or in a `{id}` subfolder (see `games_in_subfolders`); if both layouts have
+/// a build, the newer one wins.
+pub fn find_game_path(game_id: &str) -> Result<Option<PathBuf>, String> {
     let games_dir = config::get_games_dir()?;
+    Ok([games_dir.join(game_id), games_dir]
+        .iter()
+        .filter_map(|dir| find_game_in(dir, game_id))
+        .max_by_key(|path| modified_secs(path)))
+}
 
+fn find_game_in(dir: &Path, game_id: &str) -> Option<PathBuf> {
     // Check for standard format first
-    let standard_path = games_dir.join(format!("{}.swf", game_id));
+    let standard_path = dir.join(format!("{}.swf", game_id));
     if standard_path.exists() {
-        return Ok(Some(standard_path));
+        return Some(standard_path);
     }
 
-    // Look for versioned files
-    if let Ok(entries) = fs::read_dir(&games_dir) {
-        let prefix = format!("{}-v", game_id);
-        let mut latest_path: Option<PathBuf> = None;
-        let mut latest_time = std::time::SystemTime::UNIX_EPOCH;
+    // Look for versioned files, newest first
+    find_versioned_games(dir, game_id)
+        .into_iter()
+        .next()
+        .map(|(path, _)| path)
+}
+
+/// Directory new downloads of a game go to: its own subfolder when
+/// `games_in_subfolders` is on, otherwise the games directory itself
+pub fn game_dir(game_id: &str, settings: &Settings) -> Result<PathBuf, String> {
+    let games_dir = config::get_games_dir()?;
+    Ok(if settings.games_in_subfolders.unwrap_or(false) {
+        games_dir.join(game_id)
+    } else {
+        games_dir
+    })
+}
+
+/// List `{id}-v*.swf` files in the games directory, newest (by mtime) first
+fn find_versioned_games(games_dir: &Path, game_id: &str) -> Vec<(PathBuf, SystemTime)> {
+    let prefix = format!("{}-v", game_id);
+    let mut versions = Vec::new();
 
+    if let Ok(entries) = fs::read_dir(games_dir) {
         for entry in entries.flatten() {
             let path = entry.path();
             if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
                 if name.starts_with(&prefix) && name.ends_with(".swf") {
-                    if let Ok(metadata) = path.metadata() {
-                        if let Ok(modified) = metadata.modified() {
-                            if modified > latest_time {
-                                latest_time = modified;
-                                latest_path = Some(path);
-                            }
-                        }
+                    if let Ok(modified) = path.metadata().and_then(|m| m.modified()) {
+                        versions.push((path, modified));
                     }
                 }
             }
         }
+    }
+
+    versions.sort_by_key(|(_, modified)| std::cmp::Reverse(*modified));
+    versions
+}
+
+/// Seconds since the epoch for a file's modification time, or 0 if unknown
+fn modified_secs(path: &Path) -> u64 {
+    path.metadata()
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Check that a file starts with one of the SWF signatures
+/// (FWS = uncompressed, CWS = zlib, ZWS = LZMA)
+pub fn is_valid_swf(path: &Path) -> bool {
+    let mut signature = [0u8; 3];
+    fs::File::open(path)
+        .and_then(|mut f| f.read_exact(&mut signature))
+        .map(|_| matches!(&signature, b"FWS" | b"CWS" | b"ZWS"))
+        .unwrap_or(false)
+}
+
+/// Rename an unversioned `{id}.swf` to `{id}-v{mtime}.swf` so it is kept as an
+/// older build once versioned downloads start
+fn archive_unversioned_game(games_dir: &Path, game_id: &str) -> Result<(), String> {
+    let standard_path = games_dir.join(format!("{}.swf", game_id));
+    if !standard_path.exists() {
+        return Ok(());
+    }
+
+    let archived_path = games_dir.join(format!(
+        "{}-v{}.swf",
+        game_id,
+        modified_secs(&standard_path)
+    ));
+    fs::rename(&standard_path, &archived_path)
+        .map_err(|e| errors::io_error("Failed to archive previous version", &e))
+}
+
+/// Classify a games-directory file by the known game it belongs to: Some(true)
+/// for a `{id}-v*.swf` build, Some(false) for `{id}.swf` or its `.bak`, and
+/// None for files that don't belong to any known game
+fn classify_game_file(name: &str, game_ids: &[&String]) -> Option<bool> {
+    let stem = name
+        .strip_suffix(".swf.bak")
+        .or_else(|| name.strip_suffix(".swf"))?;
+    if game_ids.iter().any(|id| id.as_str() == stem) {
+        return Some(false);
+    }
+
+    let (id, version) = stem.rsplit_once("-v")?;
+    let is_version = !version.is_empty() && version.chars().all(|c| c.is_ascii_digit());
+    (is_version && game_ids.iter().any(|known| known.as_str() == id)).then_some(true)
+}
+
+#[derive(serde::Serialize)]
+pub struct PrunableFile {
+    pub path: String,
+    pub size: u64,
+}
+
+/// Find files in the games directory that don't belong to a known game: stale
+/// `.part` files, unknown files, and older `{id}-v*.swf` builds unless
+/// `keep_old_versions` is on. A `.part.meta` resume record is kept or pruned
+/// with its `.part`. Each game's `<games>/<id>/` subfolder is checked for stale
+/// `.part` files and old builds of that game; other files there are left
+/// alone. The active build and `.bak` of each game are always kept. Deletes
+/// them unless `dry_run` is set, and returns the list.
+#[tauri::command]
+pub fn prune_games_dir(
+    dry_run: bool,
+    config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+    downloads: tauri::State<'_, ActiveDownloads>,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
+) -> Result<Vec<PrunableFile>, String> {
+    let games_dir = config::get_games_dir()?;
+    if !games_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let keep_old_versions = config::lock_settings(&settings)
+        .keep_old_versions
+        .unwrap_or(false);
+    let mut versions = config::lock_versions(&versions);
+
+    let game_ids = config.game_ids();
+    let active_builds: Vec<PathBuf> = game_ids
+        .iter()
+        .filter_map(|id| find_game_path(id).ok().flatten())
+        .collect();
+
+    let entries =
+        fs::read_dir(&games_dir).map_err(|e| format!("Failed to read games directory: {}", e))?;
+    // Game subfolders only hold that game's files
+    let subfolder_entries = game_ids.iter().flat_map(|id| {
+        let known = [*id];
+        fs::read_dir(games_dir.join(id.as_str()))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(move |entry| (entry, Some(known)))
+    });
+    let entries = entries
+        .flatten()
+        .map(|entry| (entry, None))
+        .chain(subfolder_entries);
+
+    let mut prunable = Vec::new();
+    for (entry, subfolder_game) in entries {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+
+        let prune = if name == "version.json" || active_builds.contains(&path) {
+            false
+        } else if name.ends_with(".part") {
+            !downloads.is_writing(&path)
+        } else if let Some(part) = name.strip_suffix(".meta").filter(|n| n.ends_with(".part")) {
+            // The resume record goes with its `.part`
+            !downloads.is_writing(&path.with_file_name(part))
+        } else {
+            let ids = subfolder_game.as_ref().map_or(&game_ids[..], |id| &id[..]);
+            match classify_game_file(&name, ids) {
+                Some(versioned) => versioned && !keep_old_versions,
+                None => subfolder_game.is_none(),
+            }
+        };
+        if !prune {
+            continue;
+        }
+
+        if !dry_run {
+            fs::remove_file(&path).map_err(|e| format!("Failed to remove {}: {}", name, e))?;
+            integrity::forget(&mut versions, &path);
+        }
+        prunable.push(PrunableFile {
+            path: path.to_string_lossy().into_owned(),
+            size: metadata.len(),
+        });
+    }
+
+    if !dry_run && !prunable.is_empty() {
+        config::save_versions(&versions)?;
+    }
+
+    prunable.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(prunable)
+}
+
+/// Display name, description and URLs of a game from the catalog
+#[tauri::command]
+pub fn get_game_metadata(
+    game_id: String,
+    config: tauri::State<'_, AppConfig>,
+) -> Result<GameEntry, String> {
+    config
+        .games
+        .get(&game_id)
+        .cloned()
+        .ok_or_else(|| format!("Game '{}' not found in configuration", game_id))
+}
+
+#[derive(serde::Serialize)]
+pub struct SaveInfo {
+    /// Ruffle save directory that was searched
+    pub storage_dir: String,
+    pub exists: bool,
+    /// The most recently written save file of the game
+    pub path: Option<String>,
+    pub size: u64,
+    /// Milliseconds since the Unix epoch
+    pub modified_ms: Option<u64>,
+    /// Number of save files found for the game
+    pub file_count: usize,
+}
+
+/// A save file with its size and modification time
+type SaveFile = (PathBuf, u64, SystemTime);
+
+/// A game's save files (`.sol` SharedObjects) for one player, newest first,
+/// along with the directory that was searched.
+///
+/// Ruffle keeps them under a directory named after the host of the URL the
+/// game is spoofed as. The Flash projector keeps those of local files under
+/// `#SharedObjects/<random>/localhost/<path of the SWF>/`, so Flash saves are
+/// matched by the game file's name appearing in their path.
+fn find_save_files(
+    game_id: &str,
+    use_ruffle: bool,
+    config: &AppConfig,
+    settings: &Settings,
+) -> Result<(PathBuf, Vec<SaveFile>), String> {
+    let (storage_dir, search_dir, swf_name) = if use_ruffle {
+        let game_url = config
+            .resolve_game_url(game_id, settings)
+            .ok_or_else(|| format!("Game '{}' not found in configuration", game_id))?;
+        let host = reqwest::Url::parse(&game_url)
+            .ok()
+            .and_then(|url| url.host_str().map(|h| h.to_string()))
+            .ok_or_else(|| format!("Game URL {} has no host", config::redact_url(&game_url)))?;
+        let storage_dir = config::resolve_ruffle_storage_dir(settings)?;
+        let search_dir = storage_dir.join(host);
+        (storage_dir, search_dir, None)
+    } else {
+        let game_path = find_game_path(game_id)?
+            .ok_or_else(|| format!("'{}' isn't downloaded, so it has no Flash saves", game_id))?;
+        let storage_dir = config::get_flash_shared_objects_dir()?;
+        (
+            storage_dir.clone(),
+            storage_dir,
+            game_path.file_name().map(|n| n.to_owned()),
+        )
+    };
+
+    let mut files: Vec<SaveFile> = walkdir::WalkDir::new(&search_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "sol"))
+        .filter(|e| {
+            swf_name
+                .as_ref()
+                .is_none_or(|name| e.path().iter().any(|part| part == name.as_os_str()))
+        })
+        .filter_map(|e| {
+            let metadata = e.metadata().ok()?;
+            Some((e.into_path(), metadata.len(), metadata.modified().ok()?))
+        })
+        .collect();
+    files.sort_by_key(|(_, _, modified)| std::cmp::Reverse(*modified));
+    Ok((storage_dir, files))
+}
+
+/// Find a game's Ruffle save data and report the newest `.sol` file. Flash
+/// Player saves aren't covered, and the save contents aren't parsed.
+#[tauri::command]
+pub fn get_save_info(
+    game_id: String,
+    config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+) -> Result<SaveInfo, String> {
+    let settings = config::lock_settings(&settings).clone();
+    let (storage_dir, files) = find_save_files(&game_id, true, &config, &settings)?;
+
+    let newest = files.first();
+    Ok(SaveInfo {
+        storage_dir: storage_dir.to_string_lossy().into_owned(),
+        exists: newest.is_some(),
+        path: newest.map(|(path, _, _)| path.to_string_lossy().into_owned()),
+        size: newest.map_or(0, |(_, size, _)| *size),
+        modified_ms: newest.and_then(|(_, _, modified)| {
+            modified
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|d| d.as_millis() as u64)
+        }),
+        file_count: files.len(),
+    })
+}
+
+/// Whether `bytes` look like a SharedObject: a 0x00BF marker, the data
+/// length and a `TCSO` signature
+fn is_sol(bytes: &[u8]) -> bool {
+    bytes.len() >= 10 && bytes[..2] == [0x00, 0xBF] && &bytes[6..10] == b"TCSO"
+}
+
+/// The SharedObject name stored after the `TCSO` signature, which Flash and
+/// Ruffle also use as the file name
+fn sol_name(bytes: &[u8]) -> Option<String> {
+    let len = u16::from_be_bytes(bytes.get(16..18)?.try_into().ok()?) as usize;
+    let name = std::str::from_utf8(bytes.get(18..18 + len)?).ok()?;
+    Some(name.to_string()).filter(|name| !name.is_empty())
+}
+
+/// A game's newest save file for the player it would launch with
+fn current_save(
+    game_id: &str,
+    player: Option<&str>,
+    config: &AppConfig,
+    settings: &Mutex<Settings>,
+) -> Result<Option<PathBuf>, String> {
+    let settings = config::lock_settings(settings).clone();
+    let use_ruffle = selects_ruffle(player, &settings)?;
+    let (_, files) = find_save_files(game_id, use_ruffle, config, &settings)?;
+    Ok(files.into_iter().next().map(|(path, _, _)| path))
+}
 
-        if latest_path.is_some() {
-            return Ok(latest_path);
+/// Copy a game's newest save file, unchanged, to `dest_path` for use with
+/// external save editors. `dest_path` may be a directory, in which case the
+/// save keeps its file name. `player` ("flash" or "ruffle") picks whose saves
+/// are exported, defaulting to the configured player. Returns the written path.
+#[tauri::command]
+pub fn export_save(
+    game_id: String,
+    dest_path: String,
+    player: Option<String>,
+    config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+) -> Result<String, String> {
+    let save = current_save(&game_id, player.as_deref(), &config, &settings)?
+        .ok_or_else(|| format!("'{}' has no save data yet", game_id))?;
+
+    let mut dest = PathBuf::from(dest_path);
+    if dest.is_dir() {
+        if let Some(name) = save.file_name() {
+            dest.push(name);
         }
     }
+    fs::copy(&save, &dest).map_err(|e| errors::io_error("Failed to export save", &e))?;
+    Ok(dest.to_string_lossy().into_owned())
+}
+
+/// Replace one of a game's save files with `source_path`, e.g. one edited
+/// with an external tool. The file must be a SharedObject, and replaces the
+/// game's newest save with the same SharedObject name (the source's file name
+/// if the header can't be read). The save being replaced is first copied to
+/// the save backups directory; the game needs to have saved at least once so
+/// there's a save to replace. Returns the backup path.
+#[tauri::command]
+pub fn import_save(
+    game_id: String,
+    source_path: String,
+    player: Option<String>,
+    config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+    running: tauri::State<'_, RunningGames>,
+) -> Result<String, String> {
+    if running.running_ids().contains(&game_id) {
+        return Err(format!(
+            "'{}' is running. Close it before importing a save, or it will overwrite the import.",
+            game_id
+        ));
+    }
+
+    let bytes = fs::read(&source_path)
+        .map_err(|e| errors::io_error(&format!("Failed to read {}", source_path), &e))?;
+    if !is_sol(&bytes) {
+        return Err(format!(
+            "{} is not a Flash save file (.sol)",
+            Path::new(&source_path).display()
+        ));
+    }
+
+    let name = sol_name(&bytes)
+        .or_else(|| {
+            Path::new(&source_path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().into_owned())
+        })
+        .unwrap_or_default();
+    let save = {
+        let settings = config::lock_settings(&settings).clone();
+        let use_ruffle = selects_ruffle(player.as_deref(), &settings)?;
+        let (_, files) = find_save_files(&game_id, use_ruffle, &config, &settings)?;
+        files
+            .into_iter()
+            .map(|(path, _, _)| path)
+            .find(|path| path.file_stem().is_some_and(|stem| stem == name.as_str()))
+    }
+    .ok_or_else(|| {
+        format!(
+            "'{}' has no save named '{}' to replace. Play it and save once, then import again.",
+            game_id, name
+        )
+    })?;
+
+    let backups_dir = config::get_save_backups_dir()?;
+    fs::create_dir_all(&backups_dir)
+        .map_err(|e| errors::io_error("Failed to create save backups directory", &e))?;
+    let stem = save
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let backup = backups_dir.join(format!(
+        "{}-{}-{}.sol",
+        game_id,
+        stem,
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::copy(&save, &backup).map_err(|e| errors::io_error("Failed to back up save", &e))?;
+
+    // Write next to the save and rename, so a failed write can't truncate it
+    let partial = save.with_extension("sol.import");
+    fs::write(&partial, &bytes)
+        .and_then(|_| fs::rename(&partial, &save))
+        .map_err(|e| {
+            let _ = fs::remove_file(&partial);
+            errors::io_error("Failed to import save", &e)
+        })?;
+    Ok(backup.to_string_lossy().into_owned())
+}
+
+#[derive(serde::Serialize)]
+pub struct GameListing {
+    pub id: String,
+    #[serde(flatten)]
+    pub entry: GameEntry,
+}
+
+/// Every game in the catalog in a stable display order (see
+/// [`AppConfig::display_order`]), so the list doesn't shuffle between runs
+#[tauri::command]
+pub fn list_games_ordered(config: tauri::State<'_, AppConfig>) -> Vec<GameListing> {
+    config
+        .display_order()
+        .into_iter()
+        .map(|id| GameListing {
+            id: id.clone(),
+            entry: config.games[id].clone(),
+        })
+        .collect()
+}
+
+/// Largest game icon that will be downloaded
+const MAX_ICON_SIZE: usize = 2 * 1024 * 1024; // 2 MB
+
+/// Icon formats accepted from `icon_url`, as file extensions
+const ICON_EXTENSIONS: [&str; 3] = ["png", "jpg", "webp"];
+
+/// File extension for an image by its signature, if it's a supported icon format
+fn icon_extension(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("jpg")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("webp")
+    } else {
+        None
+    }
+}
+
+/// Local path of a game's icon, downloading it into the icon cache on first
+/// request. Returns None when the game has no `icon_url`.
+#[tauri::command]
+pub async fn get_game_icon(
+    game_id: String,
+    config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+) -> Result<Option<String>, String> {
+    let game = config
+        .games
+        .get(&game_id)
+        .ok_or_else(|| format!("Game '{}' not found in configuration", game_id))?;
+    let Some(icon_url) = &game.icon_url else {
+        return Ok(None);
+    };
+
+    let icons_dir = config::get_icons_dir()?;
+    let cached = ICON_EXTENSIONS
+        .iter()
+        .map(|ext| icons_dir.join(format!("{}.{}", game_id, ext)))
+        .find(|path| path.exists());
+
+    let path = match cached {
+        Some(path) => path,
+        None => {
+            let client = crate::download::client_builder(&settings)?
+                .timeout(Duration::from_secs(30))
+                .build()
+                .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+            let response = client.get(icon_url).send().await.map_err(|e| {
+                format!(
+                    "Request to {} failed: {}",
+                    config::redact_url(icon_url),
+                    e.without_url()
+                )
+            })?;
+            if !response.status().is_success() {
+                return Err(format!(
+                    "HTTP error: {} from {}",
+                    response.status(),
+                    config::redact_url(icon_url)
+                ));
+            }
+            if response
+                .content_length()
+                .is_some_and(|len| len > MAX_ICON_SIZE as u64)
+            {
+                return Err("Game icon is too large".to_string());
+            }
+
+            let bytes = response
+                .bytes()
+                .await
+                .map_err(|e| format!("Failed to download game icon: {}", e.without_url()))?;
+            if bytes.len() > MAX_ICON_SIZE {
+                return Err("Game icon is too large".to_string());
+            }
+            let ext = icon_extension(&bytes)
+                .ok_or_else(|| "Game icon is not a PNG, JPEG or WebP image".to_string())?;
+
+            fs::create_dir_all(&icons_dir)
+                .map_err(|e| format!("Failed to create icons directory: {}", e))?;
+            let path = icons_dir.join(format!("{}.{}", game_id, ext));
+            fs::write(&path, &bytes).map_err(|e| format!("Failed to save game icon: {}", e))?;
+            path
+        }
+    };
 
-    Ok(None)
+    path.to_str()
+        .map(|s| Some(s.to_string()))
+        .ok_or_else(|| "Invalid path".to_string())
 }
 
 #[tauri::command]
@@ -61,17 +597,47 @@ pub async fn download_game(
     window: Window,
     game_id: String,
     config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
 ) -> Result<String, String> {
-    let url = config
-        .game_urls
-        .get(&game_id)
-        .ok_or_else(|| format!("Game '{}' not found in configuration", game_id))?;
+    // Held until the install is recorded; a second download would back up
+    // and replace the file under the first
+    let download = window.state::<ActiveDownloads>().begin(&window, &game_id)?;
+
+    let (url, keep_old_versions, bypass_cache, games_dir) = {
+        let settings = config::lock_settings(&settings);
+        (
+            config.resolve_game_url(&game_id, &settings),
+            settings.keep_old_versions.unwrap_or(false),
+            settings.bypass_cache.unwrap_or(false),
+            game_dir(&game_id, &settings)?,
+        )
+    };
+    let url = url.ok_or_else(|| format!("Game '{}' not found in configuration", game_id))?;
+    let source_url = url.clone();
 
-    let games_dir = config::get_games_dir()?;
     fs::create_dir_all(&games_dir)
-        .map_err(|e| format!("Failed to create games directory: {}", e))?;
+        .map_err(|e| errors::io_error("Failed to create games directory", &e))?;
 
-    let dest_path = games_dir.join(format!("{}.swf", game_id));
+    let timestamp = chrono::Utc::now().timestamp();
+    let dest_path = if keep_old_versions {
+        archive_unversioned_game(&games_dir, &game_id)?;
+        games_dir.join(format!("{}-v{}.swf", game_id, timestamp))
+    } else {
+        games_dir.join(format!("{}.swf", game_id))
+    };
+    let mut headers = config
+        .game_headers
+        .get(&game_id)
+        .cloned()
+        .unwrap_or_default();
+    let url = if bypass_cache {
+        headers.insert("Cache-Control".to_string(), "no-cache".to_string());
+        headers.insert("Pragma".to_string(), "no-cache".to_string());
+        cache_busted_url(&url, timestamp)?
+    } else {
+        url
+    };
 
     // Emit initial progress
     let _ = window.emit(
@@ -81,19 +647,138 @@ pub async fn download_game(
             progress: 0,
             downloaded: 0,
             total: 0,
+            phase: DownloadPhase::Connecting,
             status: "Starting download...".to_string(),
+            seq: download::next_seq(),
         },
     );
 
+    // Only a first install may fall back to a bundled copy; an installed
+    // build is better than an older bundled one
+    let first_install = find_game_path(&game_id)?.is_none();
+
+    // Keep the current build as a .bak so a bad download can be reverted.
+    // The download itself only replaces the file once it has fully arrived.
+    let backup_path = dest_path.with_extension("swf.bak");
+    let had_previous = dest_path.exists();
+    if had_previous {
+        fs::copy(&dest_path, &backup_path)
+            .map_err(|e| errors::io_error("Failed to back up previous version", &e))?;
+    }
+
+    // Another game installed from the same URL already has this file. Reuse it
+    // for a first install; an update is downloaded so it actually gets newer.
+    let duplicate = find_duplicate_download(&versions, &game_id, &source_url);
+    let reused = match &duplicate {
+        Some((other_id, existing)) if first_install => match link_or_copy(existing, &dest_path) {
+            Ok(()) => {
+                crate::logger::log(&format!(
+                    "{} has the same URL as {}; reused its download",
+                    game_id, other_id
+                ));
+                true
+            }
+            Err(e) => {
+                crate::logger::log(&format!(
+                    "Failed to reuse the download of {} for {}, downloading instead: {}",
+                    other_id, game_id, e
+                ));
+                false
+            }
+        },
+        Some((other_id, _)) => {
+            crate::logger::log(&format!(
+                "{} has the same URL as {}, so both are downloaded separately",
+                game_id, other_id
+            ));
+            false
+        }
+        None => false,
+    };
+
     // Download the file
-    download_file_with_progress(&window, url, &dest_path, &game_id).await?;
+    let downloaded = if reused {
+        Ok(download::RemoteFile::default())
+    } else {
+        // Game SWFs change in place, which download_resumable guards against
+        download::download_resumable(
+            &download,
+            &url,
+            Some(&headers),
+            &dest_path,
+            config.expected_sizes.get(&game_id).copied(),
+        )
+        .await
+    };
+    // The bundled copy only stands in when the host can't be reached; an
+    // error response or a bad download is reported
+    let (bundled, remote) = match downloaded {
+        Ok(remote) => (false, remote),
+        Err(e) if !e.unreachable || !first_install => return Err(e.into()),
+        Err(download::DownloadError { message: e, .. }) => {
+            let bundled_sha256 = config
+                .games
+                .get(&game_id)
+                .and_then(|g| g.bundled_sha256.as_deref());
+            if !install_bundled_game(&window, &game_id, bundled_sha256, &dest_path)? {
+                return Err(e);
+            }
+            crate::logger::log(&format!(
+                "Installed bundled copy of {} after download failed: {}",
+                game_id, e
+            ));
+            (true, download::RemoteFile::default())
+        }
+    };
+
+    let _ = window.emit(
+        "download-progress",
+        DownloadProgress {
+            item: game_id.clone(),
+            progress: 100,
+            downloaded: 0,
+            total: 0,
+            phase: DownloadPhase::Verifying,
+            status: "Verifying...".to_string(),
+            seq: download::next_seq(),
+        },
+    );
+
+    // Servers sometimes answer with an HTML error page instead of the SWF
+    if !is_valid_swf(&dest_path) {
+        if had_previous {
+            let _ = fs::rename(&backup_path, &dest_path);
+        } else {
+            let _ = fs::remove_file(&dest_path);
+        }
+        let error = format!("Downloaded file for '{}' is not a valid SWF", game_id);
+        download::emit_failed(&window, &game_id, &error);
+        return Err(error);
+    }
 
     // Update version info
-    let mut versions = config::load_versions().unwrap_or_default();
-    versions
-        .games
-        .insert(game_id.clone(), chrono::Utc::now().timestamp().to_string());
-    config::save_versions(&versions)?;
+    let version = GameVersion {
+        installed_at: Some(chrono::Utc::now().to_rfc3339()),
+        last_modified: remote.last_modified,
+        etag: remote.etag,
+        ..GameVersion::new(if bundled {
+            BUNDLED_VERSION.to_string()
+        } else {
+            timestamp.to_string()
+        })
+    };
+    let hashes = integrity::hash_tree_async(dest_path.clone()).await?;
+    config::update_versions(&versions, |versions| {
+        versions.games.insert(game_id.clone(), version);
+        if bundled {
+            versions.game_sources.remove(&game_id);
+        } else {
+            versions.game_sources.insert(game_id.clone(), source_url);
+        }
+        integrity::record(versions, hashes);
+    })?;
+
+    download::run_post_download_hook(&settings, &game_id, &dest_path);
 
     // Emit completion
     let _ = window.emit(
@@ -103,7 +788,9 @@ pub async fn download_game(
             progress: 100,
             downloaded: 0,
             total: 0,
+            phase: DownloadPhase::Complete,
             status: "Download complete".to_string(),
+            seq: download::next_seq(),
         },
     );
 
@@ -113,167 +800,1587 @@ pub async fn download_game(
         .ok_or_else(|| "Invalid path".to_string())
 }
 
-use std::sync::Mutex;
+#[derive(serde::Serialize)]
+pub struct GameUpdate {
+    pub installed: bool,
+    pub update_available: bool,
+}
 
+/// Ask the server whether a newer build of an installed game exists. The
+/// server's ETag is compared with the one recorded at download; without one,
+/// its Last-Modified is compared with when the installed build was
+/// downloaded, falling back to comparing Content-Length with the file size.
 #[tauri::command]
-pub async fn launch_game(
+pub async fn check_game_update(
     game_id: String,
     config: tauri::State<'_, AppConfig>,
     settings: tauri::State<'_, Mutex<Settings>>,
-) -> Result<(), String> {
-    let settings = match settings.lock() {
-        Ok(s) => s,
-        Err(p) => p.into_inner(),
+    versions: tauri::State<'_, Mutex<GameVersions>>,
+) -> Result<GameUpdate, String> {
+    let Some(path) = find_game_path(&game_id)? else {
+        return Ok(GameUpdate {
+            installed: false,
+            update_available: false,
+        });
     };
 
-    // Find the game path
-    let game_path = find_game_path(&game_id)?
-        .ok_or_else(|| format!("Game '{}' not found. Please download it first.", game_id))?;
-
-    // Determine which player to use
-    let use_ruffle = settings.use_ruffle.unwrap_or(false);
+    let (_, _, response) = head_game(&game_id, &config, &settings).await?;
+    let installed = config::lock_versions(&versions)
+        .games
+        .get(&game_id)
+        .cloned();
+    let (_, matches) = compare_validators(response.headers(), installed.as_ref(), &path);
 
-    let player_path = if use_ruffle {
-        let path = config::get_ruffle_path(&config, &settings)?;
-        if !path.exists() {
-            return Err("Ruffle not installed. Please download it first.".to_string());
-        }
-        path
-    } else {
-        let path = config::get_flash_player_path(&config, &settings)?;
-        if !path.exists() {
-            return Err("Flash Player not installed. Please download it first.".to_string());
-        }
-        path
-    };
+    Ok(GameUpdate {
+        installed: true,
+        update_available: matches == Some(false),
+    })
+}
 
-    // Get game URL for Ruffle arguments
-    let game_url = config
-        .game_urls
-        .get(&game_id)
+/// Send a HEAD request for a game's download URL, returning the URL, the
+/// client used and the response
+async fn head_game(
+    game_id: &str,
+    config: &AppConfig,
+    settings: &Mutex<Settings>,
+) -> Result<(String, reqwest::Client, reqwest::Response), String> {
+    let url = config
+        .resolve_game_url(game_id, &config::lock_settings(settings))
         .ok_or_else(|| format!("Game '{}' not found in configuration", game_id))?;
+    let client = crate::download::client_builder(settings)?
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+    let mut request = client.head(&url);
+    for (name, value) in config.game_headers.get(game_id).into_iter().flatten() {
+        request = request.header(name, value);
+    }
+    let response = request.send().await.map_err(|e| {
+        format!(
+            "Request to {} failed: {}",
+            config::redact_url(&url),
+            e.without_url()
+        )
+    })?;
+    if !response.status().is_success() {
+        return Err(format!("HTTP error: {}", response.status()));
+    }
+    Ok((url, client, response))
+}
 
-    // Derive base URL (remove filename from URL)
-    let base_url = if let Some(idx) = game_url.rfind('/') {
-        &game_url[..=idx]
-    } else {
-        game_url
-    };
+/// How [`compare_game_remote`] compared the local and remote game files
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareMethod {
+    /// SHA-256 published by the server
+    Sha256,
+    /// ETag recorded at download
+    Etag,
+    /// Last-Modified against when the build was downloaded
+    LastModified,
+    /// Content-Length against the file size
+    Size,
+    /// The server sent nothing to compare with
+    None,
+}
 
-    // Launch the game
-    #[cfg(target_os = "windows")]
-    {
-        let mut cmd = Command::new(&player_path);
-
-        if use_ruffle {
-            cmd.arg(&game_path)
-                .arg("--spoof-url")
-                .arg(game_url)
-                .arg("--base")
-                .arg(base_url);
-        } else {
-            cmd.arg(&game_path);
-        }
+/// Whether the server's validators match the installed build, and which one
+/// decided it. The ETag recorded at download wins, then Last-Modified
+/// against when the build was downloaded, then Content-Length.
+fn compare_validators(
+    headers: &reqwest::header::HeaderMap,
+    installed: Option<&GameVersion>,
+    path: &Path,
+) -> (CompareMethod, Option<bool>) {
+    // When the installed build was downloaded; version.json records this for
+    // both plain and versioned builds, the file time is a fallback
+    let downloaded_at = installed
+        .and_then(|v| match v.version.as_str() {
+            // A bundled build may be arbitrarily old
+            BUNDLED_VERSION => Some(0),
+            _ => v
+                .installed_at
+                .as_deref()
+                .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                .map(|t| t.timestamp().max(0) as u64),
+        })
+        .unwrap_or_else(|| modified_secs(path));
 
-        cmd.spawn()
-            .map_err(|e| format!("Failed to launch game: {}", e))?;
-    }
+    let header = |name| headers.get(name).and_then(|v| v.to_str().ok());
+    let etag = header(reqwest::header::ETAG);
+    let installed_etag = installed.and_then(|v| v.etag.as_deref());
 
-    #[cfg(target_os = "macos")]
-    {
-        if use_ruffle {
-            // Ruffle is a binary, not an .app bundle usually
-            Command::new(&player_path)
-                .arg(&game_path)
-                .arg("--spoof-url")
-                .arg(game_url)
-                .arg("--base")
-                .arg(base_url)
-                .spawn()
-                .map_err(|e| format!("Failed to launch game: {}", e))?;
-        } else {
-            // Flash Player is an .app bundle
-            let player_str = player_path
-                .to_str()
-                .ok_or_else(|| "Invalid player path".to_string())?;
-            let out = Command::new("open")
-                .args(["-a", player_str])
-                .arg(&game_path)
-                .spawn()
-                .map_err(|e| format!("Failed to launch game: {}", e))?;
-            let _ = out;
-        }
+    let last_modified = header(reqwest::header::LAST_MODIFIED)
+        .and_then(|v| chrono::DateTime::parse_from_rfc2822(v).ok())
+        .map(|t| t.timestamp().max(0) as u64);
+
+    // HEAD responses have no body, so read the header rather than content_length()
+    let remote_size = header(reqwest::header::CONTENT_LENGTH).and_then(|v| v.parse::<u64>().ok());
+
+    match (etag, installed_etag, last_modified, remote_size) {
+        (Some(remote), Some(installed), _, _) => (CompareMethod::Etag, Some(remote == installed)),
+        (_, _, Some(remote), _) => (CompareMethod::LastModified, Some(remote <= downloaded_at)),
+        (_, _, None, Some(remote_size)) => (
+            CompareMethod::Size,
+            fs::metadata(path).ok().map(|m| m.len() == remote_size),
+        ),
+        (_, _, None, None) => (CompareMethod::None, None),
     }
+}
 
-    #[cfg(target_os = "linux")]
-    {
-        let mut cmd = Command::new(&player_path);
-
-        if use_ruffle {
-            cmd.arg(&game_path)
-                .arg("--spoof-url")
-                .arg(game_url)
-                .arg("--base")
-                .arg(base_url);
-        } else {
-            cmd.arg(&game_path);
-        }
+/// Largest `.sha256` sidecar file read by [`compare_game_remote`]
+const MAX_CHECKSUM_FILE_SIZE: usize = 1024;
+
+/// How long [`compare_game_remote`] waits for a `.sha256` sidecar file
+const CHECKSUM_FILE_TIMEOUT: Duration = Duration::from_secs(10);
 
-        cmd.spawn()
-            .map_err(|e| format!("Failed to launch game: {}", e))?;
+/// SHA-256 of the file, as lowercase hex, from response headers: an RFC 9530
+/// `Repr-Digest` or RFC 3230 `Digest` with `sha-256`, or the hashes some
+/// object stores send
+fn header_sha256(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    use base64::Engine;
+    let from_base64 = |value: &str| {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(value.trim().trim_matches(':'))
+            .ok()?;
+        (bytes.len() == 32).then(|| bytes.iter().map(|b| format!("{:02x}", b)).collect())
+    };
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+
+    for name in ["repr-digest", "digest"] {
+        let digest = header(name)
+            .into_iter()
+            .flat_map(|v| v.split(','))
+            .filter_map(|entry| entry.trim().split_once('='))
+            .find(|(alg, _)| alg.trim().eq_ignore_ascii_case("sha-256"))
+            .and_then(|(_, value)| from_base64(value));
+        if digest.is_some() {
+            return digest;
+        }
+    }
+    if let Some(digest) = header("x-amz-checksum-sha256").and_then(from_base64) {
+        return Some(digest);
     }
+    header("x-checksum-sha256")
+        .map(|v| v.trim().to_lowercase())
+        .filter(|v| is_sha256_hex(v))
+}
 
-    Ok(())
+fn is_sha256_hex(value: &str) -> bool {
+    value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit())
 }
 
-async fn download_file_with_progress(
-    window: &Window,
-    url: &str,
-    dest: &PathBuf,
-    item_name: &str,
-) -> Result<(), String> {
-    let client = reqwest::Client::new();
+/// SHA-256 from a `{url}.sha256` file next to the game, in `sha256sum`
+/// format or as a bare hash
+async fn sidecar_sha256(client: &reqwest::Client, url: &str) -> Option<String> {
+    let mut sidecar = reqwest::Url::parse(url).ok()?;
+    let path = format!("{}.sha256", sidecar.path());
+    sidecar.set_path(&path);
+
     let response = client
-        .get(url)
+        .get(sidecar)
+        .timeout(CHECKSUM_FILE_TIMEOUT)
         .send()
         .await
-        .map_err(|e| format!("Request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!("HTTP error: {}", response.status()));
+        .ok()?;
+    if !response.status().is_success()
+        || response
+            .content_length()
+            .is_some_and(|len| len > MAX_CHECKSUM_FILE_SIZE as u64)
+    {
+        return None;
     }
+    let body = response.bytes().await.ok()?;
+    if body.len() > MAX_CHECKSUM_FILE_SIZE {
+        return None;
+    }
+    let hash = String::from_utf8_lossy(&body)
+        .split_whitespace()
+        .next()?
+        .to_lowercase();
+    is_sha256_hex(&hash).then_some(hash)
+}
 
-    let total = response.content_length().unwrap_or(0);
-    let mut downloaded: u64 = 0;
-
-    let mut file = fs::File::create(dest).map_err(|e| format!("Failed to create file: {}", e))?;
-
-    let mut stream = response.bytes_stream();
-    use futures_util::StreamExt;
+#[derive(serde::Serialize)]
+pub struct RemoteComparison {
+    pub installed: bool,
+    /// Whether the installed build is the one on the server; `None` when
+    /// there was nothing to compare
+    pub matches: Option<bool>,
+    pub method: CompareMethod,
+    /// SHA-256 of the installed file, when a remote one was found
+    pub local_sha256: Option<String>,
+    pub remote_sha256: Option<String>,
+}
 
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk.map_err(|e| format!("Download error: {}", e))?;
-        file.write_all(&chunk)
-            .map_err(|e| format!("Write error: {}", e))?;
+/// Check whether the installed build of a game is the one on the server
+/// without downloading it. A SHA-256 from the response headers or a
+/// `.sha256` file next to the game is compared with the local file's hash;
+/// without one, the same ETag, Last-Modified and size checks as
+/// [`check_game_update`] are used.
+#[tauri::command]
+pub async fn compare_game_remote(
+    game_id: String,
+    config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
+) -> Result<RemoteComparison, String> {
+    let Some(path) = find_game_path(&game_id)? else {
+        return Ok(RemoteComparison {
+            installed: false,
+            matches: None,
+            method: CompareMethod::None,
+            local_sha256: None,
+            remote_sha256: None,
+        });
+    };
 
-        downloaded += chunk.len() as u64;
-        let progress = if total > 0 {
-            ((downloaded as f64 / total as f64) * 100.0) as u32
-        } else {
-            0
-        };
+    let (url, client, response) = head_game(&game_id, &config, &settings).await?;
+    let remote_sha256 = match header_sha256(response.headers()) {
+        Some(hash) => Some(hash),
+        None => sidecar_sha256(&client, &url).await,
+    };
 
-        let _ = window.emit(
-            "download-progress",
-            DownloadProgress {
-                item: item_name.to_string(),
-                progress,
-                downloaded,
-                total,
-                status: "Downloading...".to_string(),
-            },
-        );
+    if let Some(remote) = remote_sha256 {
+        let local = integrity::hash_file_async(path.clone()).await?;
+        return Ok(RemoteComparison {
+            installed: true,
+            matches: Some(local == remote),
+            method: CompareMethod::Sha256,
+            local_sha256: Some(local),
+            remote_sha256: Some(remote),
+        });
     }
 
+    let installed = config::lock_versions(&versions)
+        .games
+        .get(&game_id)
+        .cloned();
+    let (method, matches) = compare_validators(response.headers(), installed.as_ref(), &path);
+    Ok(RemoteComparison {
+        installed: true,
+        matches,
+        method,
+        local_sha256: None,
+        remote_sha256: None,
+    })
+}
+
+/// Download a game's SWF to `dest_path` without installing it: the games
+/// directory and `version.json` are left alone. Progress is reported as
+/// `download-progress` events for the item `{game_id}_export`, so it isn't
+/// mistaken for an install of the game. Returns the written path.
+#[tauri::command]
+pub async fn download_game_to(
+    window: Window,
+    game_id: String,
+    dest_path: String,
+    config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+) -> Result<String, String> {
+    let dest_path = PathBuf::from(dest_path);
+    let is_swf = dest_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("swf"));
+    if !is_swf {
+        return Err("Destination file name must end in .swf".to_string());
+    }
+    let dest_dir = dest_path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    if !dest_dir.is_dir() {
+        return Err(format!(
+            "Destination folder {} does not exist",
+            dest_dir.display()
+        ));
+    }
+    if !config::is_dir_writable(dest_dir) {
+        return Err(format!(
+            "Destination folder {} is not writable",
+            dest_dir.display()
+        ));
+    }
+
+    let url = config
+        .resolve_game_url(&game_id, &config::lock_settings(&settings))
+        .ok_or_else(|| format!("Game '{}' not found in configuration", game_id))?;
+    let headers = config
+        .game_headers
+        .get(&game_id)
+        .cloned()
+        .unwrap_or_default();
+
+    let item = format!("{}_export", game_id);
+    let download = window.state::<ActiveDownloads>().begin(&window, &item)?;
+    download_file_with_progress(
+        &download,
+        &url,
+        Some(&headers),
+        &dest_path,
+        config.expected_sizes.get(&game_id).copied(),
+    )
+    .await?;
+
+    if !is_valid_swf(&dest_path) {
+        let _ = fs::remove_file(&dest_path);
+        let error = format!("Downloaded file for '{}' is not a valid SWF", game_id);
+        download::emit_failed(&window, &item, &error);
+        return Err(error);
+    }
+
+    download::run_post_download_hook(&settings, &item, &dest_path);
+
+    let _ = window.emit(
+        "download-progress",
+        DownloadProgress {
+            item,
+            progress: 100,
+            downloaded: 0,
+            total: 0,
+            phase: DownloadPhase::Complete,
+            status: "Download complete".to_string(),
+            seq: download::next_seq(),
+        },
+    );
+
+    dest_path
+        .to_str()
+        .map(|s| s.to_string())
+        .ok_or_else(|| "Invalid path".to_string())
+}
+
+/// Another installed game that was downloaded from `url`, and its file
+fn find_duplicate_download(
+    versions: &Mutex<GameVersions>,
+    game_id: &str,
+    url: &str,
+) -> Option<(String, PathBuf)> {
+    let sources = config::lock_versions(versions).game_sources.clone();
+    sources
+        .into_iter()
+        .filter(|(id, source)| id != game_id && source == url)
+        .find_map(|(id, _)| {
+            let path = find_game_path(&id).ok().flatten()?;
+            is_valid_swf(&path).then_some((id, path))
+        })
+}
+
+/// Hard link `existing` to `dest`, copying when the file system can't link
+fn link_or_copy(existing: &Path, dest: &Path) -> Result<(), String> {
+    let _ = fs::remove_file(dest);
+    if fs::hard_link(existing, dest).is_ok() {
+        return Ok(());
+    }
+    fs::copy(existing, dest)
+        .map(|_| ())
+        .map_err(|e| errors::io_error("Failed to copy existing download", &e))
+}
+
+/// Version recorded for games installed from the bundled copy
+pub const BUNDLED_VERSION: &str = "bundled";
+
+/// Copy the game's bundled `games/{id}.swf` from the app resources to `dest`,
+/// verifying it against `expected_sha256`. Returns false when there is no
+/// bundled copy or no checksum to verify it with.
+fn install_bundled_game(
+    window: &Window,
+    game_id: &str,
+    expected_sha256: Option<&str>,
+    dest: &Path,
+) -> Result<bool, String> {
+    let Some(expected_sha256) = expected_sha256 else {
+        return Ok(false);
+    };
+    let Ok(resource_dir) = window.path().resource_dir() else {
+        return Ok(false);
+    };
+    let source = resource_dir.join("games").join(format!("{}.swf", game_id));
+    if !source.is_file() {
+        return Ok(false);
+    }
+
+    let tmp_path = dest.with_extension("part");
+    fs::copy(&source, &tmp_path)
+        .map_err(|e| format!("Failed to copy bundled {}: {}", game_id, e))?;
+    let hash = integrity::hash_file(&tmp_path)?;
+    if !hash.eq_ignore_ascii_case(expected_sha256) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(format!(
+            "Bundled copy of {} failed checksum verification",
+            game_id
+        ));
+    }
+    fs::rename(&tmp_path, dest)
+        .map_err(|e| format!("Failed to install bundled {}: {}", game_id, e))?;
+    Ok(true)
+}
+
+/// Append a throwaway query parameter so intermediary caches can't serve a stale SWF
+fn cache_busted_url(url: &str, timestamp: i64) -> Result<String, String> {
+    let mut url = reqwest::Url::parse(url)
+        .map_err(|e| format!("Invalid game URL {}: {}", config::redact_url(url), e))?;
+    url.query_pairs_mut()
+        .append_pair("_", &timestamp.to_string());
+    Ok(url.into())
+}
+
+/// Revert a game to the build it had before the last download.
+/// Swaps `{id}.swf` with its `.bak`, or for versioned downloads marks the
+/// second-newest `{id}-v*.swf` as newest. Returns the version now active.
+#[tauri::command]
+pub fn rollback_game(
+    game_id: String,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
+) -> Result<String, String> {
+    // Roll back in whichever layout holds the active build
+    let games_dir = match find_game_path(&game_id)?.as_deref().and_then(Path::parent) {
+        Some(dir) => dir.to_path_buf(),
+        None => config::get_games_dir()?,
+    };
+    let standard_path = games_dir.join(format!("{}.swf", game_id));
+
+    let version = if standard_path.exists() {
+        let backup_path = standard_path.with_extension("swf.bak");
+        if !backup_path.exists() {
+            return Err(format!(
+                "No previous version of '{}' to roll back to",
+                game_id
+            ));
+        }
+
+        // Swap the active build and the backup so the rollback can be undone
+        let swap_path = standard_path.with_extension("swf.swap");
+        fs::rename(&standard_path, &swap_path)
+            .and_then(|_| fs::rename(&backup_path, &standard_path))
+            .and_then(|_| fs::rename(&swap_path, &backup_path))
+            .map_err(|e| format!("Failed to restore previous version: {}", e))?;
+
+        modified_secs(&standard_path).to_string()
+    } else {
+        let versioned = find_versioned_games(&games_dir, &game_id);
+        let (previous_path, _) = versioned
+            .get(1)
+            .ok_or_else(|| format!("No previous version of '{}' to roll back to", game_id))?;
+
+        // find_game_path picks the newest file, so touching it makes it active
+        fs::File::options()
+            .write(true)
+            .open(previous_path)
+            .and_then(|f| f.set_modified(SystemTime::now()))
+            .map_err(|e| format!("Failed to activate previous version: {}", e))?;
+
+        previous_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| s.strip_prefix(&format!("{}-v", game_id)))
+            .map(|s| s.to_string())
+            .ok_or_else(|| "Invalid versioned game filename".to_string())?
+    };
+
+    let hashes = find_game_path(&game_id)
+        .ok()
+        .flatten()
+        .map(|path| integrity::hash_tree(&path));
+    config::update_versions(&versions, |versions| {
+        if let Some(hashes) = hashes {
+            integrity::record(versions, hashes);
+        }
+        versions
+            .games
+            .insert(game_id, GameVersion::new(version.clone()));
+    })?;
+
+    Ok(version)
+}
+
+/// Lowercase letters and digits of a name, so `PTD1_Hacked`, `PTD 1 Hacked`
+/// and `ptd1-hacked` compare equal
+fn normalize_game_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Known game a file name belongs to, matched against each game's id,
+/// display name and the file name of its download URL
+fn match_game_file(file_stem: &str, config: &AppConfig) -> Option<String> {
+    let stem = normalize_game_name(file_stem);
+    config.game_ids().into_iter().find_map(|id| {
+        let entry = &config.games[id];
+        let url_stem = entry
+            .url
+            .rsplit('/')
+            .next()
+            .and_then(|name| name.strip_suffix(".swf"))
+            .unwrap_or_default();
+        [id.as_str(), entry.display_name.as_str(), url_stem]
+            .iter()
+            .any(|name| !name.is_empty() && normalize_game_name(name) == stem)
+            .then(|| id.clone())
+    })
+}
+
+#[derive(serde::Serialize)]
+pub struct ImportedGame {
+    pub path: String,
+    pub game_id: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct ImportProblem {
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Default, serde::Serialize)]
+pub struct ImportReport {
+    pub imported: Vec<ImportedGame>,
+    /// Files left alone: unrecognized names and games already installed
+    pub skipped: Vec<ImportProblem>,
+    /// Files that aren't valid SWFs or couldn't be copied
+    pub failed: Vec<ImportProblem>,
+}
+
+/// Copy the SWFs in `source_dir` (e.g. from the old launcher) into the games
+/// directory. File names are matched to known games by id, display name or
+/// download file name; unrecognized files and games that are already
+/// installed are skipped. The source files are left in place.
+#[tauri::command(async)]
+pub fn import_games_folder(
+    source_dir: String,
+    config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
+) -> Result<ImportReport, String> {
+    let entries = fs::read_dir(&source_dir)
+        .map_err(|e| errors::io_error("Failed to read import folder", &e))?;
+    let mut files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("swf"))
+        })
+        .collect();
+    files.sort();
+
+    let settings = config::lock_settings(&settings).clone();
+    let mut report = ImportReport::default();
+    for path in files {
+        let display = path.to_string_lossy().into_owned();
+        let problem = |reason: String| ImportProblem {
+            path: display.clone(),
+            reason,
+        };
+
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let Some(game_id) = match_game_file(&stem, &config) else {
+            report
+                .skipped
+                .push(problem("File name doesn't match a known game".to_string()));
+            continue;
+        };
+        if find_game_path(&game_id)?.is_some() {
+            report
+                .skipped
+                .push(problem(format!("'{}' is already installed", game_id)));
+            continue;
+        }
+        if !is_valid_swf(&path) {
+            report.failed.push(problem("Not a valid SWF".to_string()));
+            continue;
+        }
+
+        let dest_dir = game_dir(&game_id, &settings)?;
+        let dest = dest_dir.join(format!("{}.swf", game_id));
+        let copied = fs::create_dir_all(&dest_dir).and_then(|_| fs::copy(&path, &dest));
+        if let Err(e) = copied {
+            report
+                .failed
+                .push(problem(errors::io_error("Failed to copy", &e)));
+            continue;
+        }
+
+        let hashes = integrity::hash_tree(&dest);
+        config::update_versions(&versions, |versions| {
+            versions.games.insert(
+                game_id.clone(),
+                GameVersion::new(modified_secs(&dest).to_string()),
+            );
+            integrity::record(versions, hashes);
+        })?;
+        report.imported.push(ImportedGame {
+            path: display,
+            game_id,
+        });
+    }
+
+    Ok(report)
+}
+
+/// Launch a game. Refuses if the game is already running, unless `force` is
+/// set, in which case the running instance is stopped first. With
+/// `auto_retry_launch` a player that fails right away is started once more.
+#[tauri::command]
+pub async fn launch_game(
+    window: Window,
+    game_id: String,
+    player: Option<String>,
+    force: Option<bool>,
+    config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
+) -> Result<(), String> {
+    let (mut cmd, use_ruffle, auto_retry) = {
+        let settings = config::lock_settings(&settings);
+        (
+            launch_command(&game_id, player.as_deref(), &config, &settings)?,
+            selects_ruffle(player.as_deref(), &settings)?,
+            settings.auto_retry_launch.unwrap_or(false),
+        )
+    };
+
+    let launch = spawn_game(
+        &window,
+        &window.state(),
+        &game_id,
+        use_ruffle,
+        force.unwrap_or(false),
+        &mut cmd,
+    )?;
+    record_launch(&game_id, &settings, &versions);
+
+    if auto_retry {
+        if let Some(status) = early_failure(&window.state(), launch).await {
+            crate::logger::log(&format!(
+                "{} failed right after launch ({}), retrying once",
+                game_id, status
+            ));
+            let retry = spawn_game(
+                &window,
+                &window.state(),
+                &game_id,
+                use_ruffle,
+                false,
+                &mut cmd,
+            )?;
+            if let Some(status) = early_failure(&window.state(), retry).await {
+                crate::logger::log(&format!(
+                    "{} failed right after launch again ({})",
+                    game_id, status
+                ));
+                return Err(format!(
+                    "'{}' exited right after starting on two attempts ({})",
+                    game_id, status
+                ));
+            }
+            crate::logger::log(&format!("{} started on the second attempt", game_id));
+        }
+    }
+
+    Ok(())
+}
+
+/// How long `auto_retry_launch` watches a launch for an immediate failure
+const RETRY_WINDOW: Duration = Duration::from_secs(2);
+
+/// Wait up to [`RETRY_WINDOW`] for a launched player to exit, returning its
+/// status if it exited with a failure
+async fn early_failure(running: &RunningGames, launch: LaunchId) -> Option<ExitStatus> {
+    let started = Instant::now();
+    while started.elapsed() < RETRY_WINDOW {
+        if let Some(status) = running.exit_status(launch) {
+            return (!status.success()).then_some(status);
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    None
+}
+
+/// Launch the game played last (`last_played`) with the default player, for a
+/// "continue" button. Returns its id.
+#[tauri::command]
+pub async fn relaunch_last(
+    window: Window,
+    config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
+) -> Result<String, String> {
+    let game_id = config::lock_settings(&settings)
+        .last_played
+        .clone()
+        .ok_or_else(|| "No game has been played yet".to_string())?;
+    if find_game_path(&game_id)?.is_none() {
+        return Err(format!(
+            "'{}' was played last but is no longer installed",
+            game_id
+        ));
+    }
+
+    launch_game(
+        window,
+        game_id.clone(),
+        None,
+        None,
+        config,
+        settings,
+        versions,
+    )
+    .await?;
+    Ok(game_id)
+}
+
+/// Start a launch command through `running`. Ruffle's stderr is captured so
+/// an early crash can be reported with it (see [`watch_for_crash`]).
+fn spawn_game(
+    window: &Window,
+    running: &RunningGames,
+    game_id: &str,
+    use_ruffle: bool,
+    force: bool,
+    cmd: &mut Command,
+) -> Result<LaunchId, String> {
+    if use_ruffle {
+        cmd.stderr(Stdio::piped());
+    }
+    let launch = running.spawn(game_id, force, cmd)?;
+    if let Some(stderr) = running.take_stderr(launch) {
+        watch_for_crash(window.clone(), game_id.to_string(), launch, stderr);
+    }
+    Ok(launch)
+}
+
+/// A player exit this soon after launch is treated as a crash
+const CRASH_WINDOW: Duration = Duration::from_secs(10);
+
+/// How long a crash watcher waits for the player to be reaped once its
+/// output has closed
+const EXIT_STATUS_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Payload of the `game-crashed` event
+#[derive(Clone, serde::Serialize)]
+pub struct GameCrash {
+    pub game_id: String,
+    /// Exit code, if the player exited rather than being killed by a signal
+    pub exit_code: Option<i32>,
+    /// Signal that killed the player (Unix only)
+    pub signal: Option<i32>,
+    /// stderr captured from the player
+    pub stderr: String,
+    /// Crashes recorded for this game, including this one
+    pub crash_count: u64,
+}
+
+/// Watch a launched player on a background thread. Its stderr closes when it
+/// exits; if that happens within [`CRASH_WINDOW`] with a failure status, the
+/// crash is counted in `crash_counts` and a `game-crashed` event is emitted.
+fn watch_for_crash(window: Window, game_id: String, launch: LaunchId, stderr: ChildStderr) {
+    thread::spawn(move || {
+        let started = Instant::now();
+        let output = Arc::new(Mutex::new(String::new()));
+        let verbose = config::lock_settings(&window.state::<Mutex<Settings>>())
+            .ruffle_log_level
+            .is_some();
+        let log_file = if verbose {
+            crate::logger::open_ruffle_log(&game_id)
+        } else {
+            None
+        };
+        let _ = capture_output(stderr, &game_id, output.clone(), log_file).join();
+        if started.elapsed() > CRASH_WINDOW {
+            return;
+        }
+
+        let Some(status) = window
+            .state::<RunningGames>()
+            .wait_for_exit(launch, EXIT_STATUS_TIMEOUT)
+        else {
+            return;
+        };
+        if status.success() {
+            return;
+        }
+
+        #[cfg(unix)]
+        let signal = std::os::unix::process::ExitStatusExt::signal(&status);
+        #[cfg(not(unix))]
+        let signal = None;
+
+        let crash_count =
+            config::update_versions(&window.state::<Mutex<GameVersions>>(), |versions| {
+                let count = versions.crash_counts.entry(game_id.clone()).or_insert(0);
+                *count += 1;
+                *count
+            })
+            .unwrap_or_else(|e| {
+                crate::logger::log(&format!("Failed to save crash count: {}", e));
+                0
+            });
+        crate::logger::log(&format!(
+            "{} crashed {}ms after launch: {}",
+            game_id,
+            started.elapsed().as_millis(),
+            status
+        ));
+
+        let stderr = match output.lock() {
+            Ok(o) => o.clone(),
+            Err(p) => p.into_inner().clone(),
+        };
+        let _ = window.emit(
+            "game-crashed",
+            GameCrash {
+                game_id,
+                exit_code: status.code(),
+                signal,
+                stderr,
+                crash_count,
+            },
+        );
+    });
+}
+
+/// The launch precondition that failed in [`safe_launch`]. Serialized as
+/// `{ kind, message }` so the UI can react to the kind.
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "kind", content = "message", rename_all = "snake_case")]
+pub enum LaunchError {
+    /// The game hasn't been downloaded
+    GameNotDownloaded(String),
+    /// The game file isn't a SWF, e.g. an HTML error page saved by a bad download
+    InvalidGameFile(String),
+    /// The selected player isn't installed
+    PlayerNotInstalled(String),
+    /// A custom player path failed [`diagnostics::check_player_paths`]
+    InvalidPlayerPath(String),
+    /// The game is already running
+    AlreadyRunning(String),
+    /// Everything checked out but the player couldn't be started
+    LaunchFailed(String),
+}
+
+/// Launch a game after checking, in order, that its file is downloaded and is
+/// a valid SWF, that the selected player is installed and its custom path
+/// looks right, and that the game isn't already running. Returns the first
+/// failed check instead of failing part way through the launch.
+#[tauri::command(async)]
+pub fn safe_launch(
+    window: Window,
+    game_id: String,
+    player: Option<String>,
+    config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+    running: tauri::State<'_, RunningGames>,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
+) -> Result<(), LaunchError> {
+    let game_path = find_game_path(&game_id)
+        .map_err(LaunchError::LaunchFailed)?
+        .ok_or_else(|| {
+            LaunchError::GameNotDownloaded(format!(
+                "Game '{}' not found. Please download it first.",
+                game_id
+            ))
+        })?;
+    if !is_valid_swf(&game_path) {
+        return Err(LaunchError::InvalidGameFile(format!(
+            "{} is not a valid SWF. Download the game again.",
+            game_path.display()
+        )));
+    }
+
+    let settings_snapshot = config::lock_settings(&settings).clone();
+    let use_ruffle =
+        selects_ruffle(player.as_deref(), &settings_snapshot).map_err(LaunchError::LaunchFailed)?;
+    installed_player_path(&game_id, use_ruffle, &config, &settings_snapshot)
+        .map_err(LaunchError::PlayerNotInstalled)?;
+
+    let player_name = if use_ruffle { "ruffle" } else { "flash" };
+    let path_problem = diagnostics::check_player_paths(&settings_snapshot)
+        .into_iter()
+        .find(|check| check.player == player_name && check.blocking);
+    if let Some(check) = path_problem {
+        return Err(LaunchError::InvalidPlayerPath(format!(
+            "{}: {}",
+            check.path,
+            check.problem.unwrap_or_default()
+        )));
+    }
+
+    running
+        .ensure_not_running(&game_id, false)
+        .map_err(LaunchError::AlreadyRunning)?;
+
+    let mut cmd = launch_command(&game_id, player.as_deref(), &config, &settings_snapshot)
+        .map_err(LaunchError::LaunchFailed)?;
+    spawn_game(&window, &running, &game_id, use_ruffle, false, &mut cmd)
+        .map_err(LaunchError::LaunchFailed)?;
+    record_launch(&game_id, &settings, &versions);
+
     Ok(())
 }
+
+#[derive(serde::Serialize)]
+pub struct GameReadiness {
+    /// A valid game file is downloaded
+    pub downloaded: bool,
+    /// Player the game would launch with: "flash" or "ruffle"
+    pub player: String,
+    pub player_installed: bool,
+    pub ready_to_play: bool,
+    /// What has to be downloaded first, as download queue items: the game id,
+    /// "flash_player" or "ruffle"
+    pub missing: Vec<String>,
+}
+
+/// Whether a game can be launched, and if not what needs downloading first,
+/// using the same file and player checks as [`safe_launch`]
+#[tauri::command]
+pub fn get_game_readiness(
+    game_id: String,
+    player: Option<String>,
+    config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+) -> Result<GameReadiness, String> {
+    if !config.games.contains_key(&game_id) {
+        return Err(format!("Game '{}' not found in configuration", game_id));
+    }
+    let downloaded = find_game_path(&game_id)?.is_some_and(|path| is_valid_swf(&path));
+
+    let settings = config::lock_settings(&settings).clone();
+    let use_ruffle = selects_ruffle(player.as_deref(), &settings)?;
+    let player_installed = installed_player_path(&game_id, use_ruffle, &config, &settings).is_ok();
+
+    let mut missing = Vec::new();
+    if !downloaded {
+        missing.push(game_id);
+    }
+    if !player_installed {
+        let item = if use_ruffle {
+            crate::queue::RUFFLE_ITEM
+        } else {
+            crate::queue::FLASH_ITEM
+        };
+        missing.push(item.to_string());
+    }
+
+    Ok(GameReadiness {
+        downloaded,
+        player: if use_ruffle { "ruffle" } else { "flash" }.to_string(),
+        player_installed,
+        ready_to_play: missing.is_empty(),
+        missing,
+    })
+}
+
+/// Remember `game_id` as the last played game and bump its play count.
+/// The game is already running, so failures are only logged.
+fn record_launch(game_id: &str, settings: &Mutex<Settings>, versions: &Mutex<GameVersions>) {
+    let updated = {
+        let mut settings = config::lock_settings(settings);
+        settings.last_played = Some(game_id.to_string());
+        settings.clone()
+    };
+    if let Err(e) = config::save_settings(&updated) {
+        crate::logger::log(&format!("Failed to save last played game: {}", e));
+    }
+
+    let counted = config::update_versions(versions, |versions| {
+        *versions.play_counts.entry(game_id.to_string()).or_insert(0) += 1;
+    });
+    if let Err(e) = counted {
+        crate::logger::log(&format!("Failed to save play count: {}", e));
+    }
+}
+
+/// How long `launch_game_debug` watches the player for an early exit
+const DEBUG_EXIT_WINDOW: Duration = Duration::from_secs(5);
+
+/// Cap on the player output kept in memory for `launch_game_debug`
+const MAX_CAPTURED_OUTPUT: usize = 64 * 1024;
+
+#[derive(serde::Serialize)]
+pub struct LaunchDebugResult {
+    /// The player exited within the watch window
+    pub exited: bool,
+    pub exit_code: Option<i32>,
+    /// stdout and stderr captured while watching, interleaved by line
+    pub output: String,
+}
+
+/// Launch a game like `launch_game`, but capture the player's stdout/stderr and
+/// watch it for a few seconds. If it exits in that time the captured output is
+/// returned; otherwise it is left running and its output goes to the launcher log.
+/// On macOS the Flash projector is spawned from its bundle, so the output
+/// captured is the projector's own.
+#[tauri::command]
+pub async fn launch_game_debug(
+    game_id: String,
+    player: Option<String>,
+    force: Option<bool>,
+    config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+    running: tauri::State<'_, RunningGames>,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
+) -> Result<LaunchDebugResult, String> {
+    running.ensure_not_running(&game_id, force.unwrap_or(false))?;
+
+    let mut cmd = {
+        let settings = config::lock_settings(&settings);
+        launch_command(&game_id, player.as_deref(), &config, &settings)?
+    };
+
+    let mut child = cmd
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            errors::spawn_error("Failed to launch game", Path::new(cmd.get_program()), &e)
+        })?;
+    record_launch(&game_id, &settings, &versions);
+
+    let output = Arc::new(Mutex::new(String::new()));
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        readers.push(capture_output(stdout, &game_id, output.clone(), None));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        readers.push(capture_output(stderr, &game_id, output.clone(), None));
+    }
+
+    let started = Instant::now();
+    while started.elapsed() < DEBUG_EXIT_WINDOW {
+        let status = child
+            .try_wait()
+            .map_err(|e| format!("Failed to check game process: {}", e))?;
+        if let Some(status) = status {
+            // The pipes close once the player exits, so the readers finish promptly
+            for reader in readers {
+                let _ = reader.join();
+            }
+            let output = match output.lock() {
+                Ok(o) => o.clone(),
+                Err(p) => p.into_inner().clone(),
+            };
+            return Ok(LaunchDebugResult {
+                exited: true,
+                exit_code: status.code(),
+                output,
+            });
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    // Still running: leave it detached, the readers keep teeing into the log
+    let output = match output.lock() {
+        Ok(o) => o.clone(),
+        Err(p) => p.into_inner().clone(),
+    };
+    running.insert(&game_id, child);
+    Ok(LaunchDebugResult {
+        exited: false,
+        exit_code: None,
+        output,
+    })
+}
+
+/// Read lines from a player pipe on a background thread, writing each to
+/// `log_file` (the launcher log if `None`) and keeping up to
+/// `MAX_CAPTURED_OUTPUT` bytes in `output`
+fn capture_output<R: Read + Send + 'static>(
+    pipe: R,
+    game_id: &str,
+    output: Arc<Mutex<String>>,
+    mut log_file: Option<fs::File>,
+) -> thread::JoinHandle<()> {
+    let game_id = game_id.to_string();
+    thread::spawn(move || {
+        for line in BufReader::new(pipe).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            match &mut log_file {
+                Some(file) => {
+                    let _ = writeln!(file, "[{}] {}", game_id, line);
+                }
+                None => crate::logger::log(&format!("[{}] {}", game_id, line)),
+            }
+            let mut output = match output.lock() {
+                Ok(o) => o,
+                Err(p) => p.into_inner(),
+            };
+            if output.len() < MAX_CAPTURED_OUTPUT {
+                output.push_str(&line);
+                output.push('\n');
+            }
+        }
+    })
+}
+
+/// Build the player command for a game, without starting it, with the
+/// `launch_env` variables set.
+/// `player` ("flash" or "ruffle") overrides `settings.use_ruffle`.
+fn launch_command(
+    game_id: &str,
+    player: Option<&str>,
+    config: &AppConfig,
+    settings: &Settings,
+) -> Result<Command, String> {
+    let mut cmd = player_command(game_id, player, config, settings)?;
+    if let Some(env) = &settings.launch_env {
+        cmd.envs(env);
+    }
+    Ok(cmd)
+}
+
+/// Whether a launch uses Ruffle; an explicit player overrides settings for
+/// this launch
+fn selects_ruffle(player: Option<&str>, settings: &Settings) -> Result<bool, String> {
+    match player {
+        Some("ruffle") => Ok(true),
+        Some("flash") => Ok(false),
+        Some(other) => Err(format!(
+            "Unknown player '{}'. Expected 'flash' or 'ruffle'",
+            other
+        )),
+        None => Ok(settings.use_ruffle.unwrap_or(false)),
+    }
+}
+
+/// Path of the selected player for a game, or an error if it isn't
+/// installed. A `game_flash_paths` entry for the game replaces the global
+/// Flash Player and must exist; it isn't silently replaced by the global one.
+fn installed_player_path(
+    game_id: &str,
+    use_ruffle: bool,
+    config: &AppConfig,
+    settings: &Settings,
+) -> Result<PathBuf, String> {
+    let flash_override = settings
+        .game_flash_paths
+        .as_ref()
+        .and_then(|paths| paths.get(game_id));
+    if let (false, Some(path)) = (use_ruffle, flash_override) {
+        let path = PathBuf::from(path);
+        if !path.exists() {
+            return Err(format!(
+                "Flash Player for '{}' not found at {}",
+                game_id,
+                path.display()
+            ));
+        }
+        Ok(path)
+    } else if use_ruffle {
+        let path = config::get_ruffle_path(config, settings)?;
+        if !path.exists() {
+            return Err("Ruffle not installed. Please download it first.".to_string());
+        }
+        Ok(path)
+    } else {
+        let path = config::get_flash_player_path(config, settings)?;
+        #[cfg(target_os = "macos")]
+        if let Err(e) = crate::flash::remount_flash_volume(&path) {
+            crate::logger::log(&format!(
+                "Warning: failed to remount Flash Player volume: {}",
+                e
+            ));
+        }
+        if !path.exists() {
+            return Err("Flash Player not installed. Please download it first.".to_string());
+        }
+        Ok(path)
+    }
+}
+
+fn player_command(
+    game_id: &str,
+    player: Option<&str>,
+    config: &AppConfig,
+    settings: &Settings,
+) -> Result<Command, String> {
+    // Recreate the data directories in case they were deleted externally
+    config::init_config()?;
+
+    // Find the game path
+    let game_path = find_game_path(game_id)?
+        .ok_or_else(|| format!("Game '{}' not found. Please download it first.", game_id))?;
+
+    let use_ruffle = selects_ruffle(player, settings)?;
+    let player_path = installed_player_path(game_id, use_ruffle, config, settings)?;
+
+    // Get game URL for Ruffle arguments
+    let game_url = config
+        .resolve_game_url(game_id, settings)
+        .ok_or_else(|| format!("Game '{}' not found in configuration", game_id))?;
+    player_command_for(
+        game_id,
+        use_ruffle,
+        &player_path,
+        &game_path,
+        game_url.as_str(),
+        config,
+        settings,
+    )
+}
+
+/// The player command for resolved paths. Paths are passed as their own
+/// arguments, never formatted into a string.
+fn player_command_for(
+    game_id: &str,
+    use_ruffle: bool,
+    player_path: &Path,
+    game_path: &Path,
+    game_url: &str,
+    config: &AppConfig,
+    settings: &Settings,
+) -> Result<Command, String> {
+    let base_url = base_url(game_url);
+
+    #[cfg(target_os = "macos")]
+    if !use_ruffle && player_path.is_dir() {
+        // Flash Player is an .app bundle. Its executable is run directly
+        // rather than through `open`, so the tracked process is the projector
+        // itself and every launch gets its own instance.
+        let mut cmd = Command::new(crate::flash::bundle_executable(player_path)?);
+        cmd.arg(game_path);
+        return Ok(cmd);
+    }
+
+    #[cfg(target_os = "linux")]
+    if !use_ruffle && settings.use_wine_flash.unwrap_or(false) {
+        let mut cmd = Command::new(crate::flash::find_wine()?);
+        cmd.arg(player_path).arg(game_path);
+        return Ok(cmd);
+    }
+
+    let mut cmd = Command::new(player_path);
+    cmd.arg(game_path);
+    if use_ruffle {
+        let mut args: Vec<OsString> = Vec::new();
+        if let Some(dir) = config::get_ruffle_storage_dir(settings)? {
+            fs::create_dir_all(&dir)
+                .map_err(|e| format!("Failed to create Ruffle storage directory: {}", e))?;
+            args.push("--save-directory".into());
+            args.push(dir.into());
+        }
+        args.extend(
+            ruffle_args(settings, game_url, base_url)
+                .into_iter()
+                .map(OsString::from),
+        );
+        if let Some(entry) = config.games.get(game_id) {
+            args.extend(entry.ruffle_load_behavior.iter().map(OsString::from));
+        }
+        if let Some(level) = &settings.ruffle_log_level {
+            cmd.env("RUST_LOG", level);
+        }
+        cmd.args(apply_flag_compat(args, &config.ruffle_flag_compat));
+    }
+
+    Ok(cmd)
+}
+
+/// How long the speed test keeps reading before measuring
+const SPEED_TEST_DURATION: Duration = Duration::from_secs(5);
+
+/// Measure download throughput in Mbps by streaming a file from the game CDN
+/// for a few seconds and discarding the bytes
+#[tauri::command]
+pub async fn test_download_speed(
+    config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+) -> Result<f64, String> {
+    let url = match &config.speed_test_url {
+        Some(url) => url.clone(),
+        None => config
+            .resolve_game_url("PTD1", &config::lock_settings(&settings))
+            .ok_or_else(|| "No speed test URL configured".to_string())?,
+    };
+    let url = url.as_str();
+
+    let client = crate::download::client_builder(&settings)?
+        .timeout(SPEED_TEST_DURATION * 2)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+    let response = client.get(url).send().await.map_err(|e| {
+        format!(
+            "Request to {} failed: {}",
+            config::redact_url(url),
+            e.without_url()
+        )
+    })?;
+
+    if !response.status().is_success() {
+        return Err(format!("HTTP error: {}", response.status()));
+    }
+
+    let mut stream = response.bytes_stream();
+    use futures_util::StreamExt;
+
+    let start = Instant::now();
+    let mut received: u64 = 0;
+
+    while let Some(remaining) = SPEED_TEST_DURATION.checked_sub(start.elapsed()) {
+        match tokio::time::timeout(remaining, stream.next()).await {
+            Ok(Some(chunk)) => {
+                let chunk = chunk.map_err(|e| format!("Download error: {}", e.without_url()))?;
+                received += chunk.len() as u64;
+            }
+            // Stream finished or the measuring window elapsed
+            Ok(None) | Err(_) => break,
+        }
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+    if received == 0 || elapsed <= 0.0 {
+        return Err("No data received during speed test".to_string());
+    }
+
+    Ok((received as f64 * 8.0) / elapsed / 1_000_000.0)
+}
+
+/// How long `set_backend` waits for the new backend to answer
+const BACKEND_CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Point all game downloads and launches at another PTD mirror. The backend is
+/// only saved once a game file can be fetched from it. Pass `None` or an empty
+/// string to go back to the configured hosts.
+#[tauri::command]
+pub async fn set_backend(
+    base: Option<String>,
+    config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+) -> Result<(), String> {
+    let base = base.map(|b| b.trim().to_string()).filter(|b| !b.is_empty());
+
+    if let Some(base) = &base {
+        config::parse_backend_base(base)?;
+        let game_id = config
+            .game_ids()
+            .first()
+            .map(|id| id.to_string())
+            .ok_or_else(|| "No games configured to check the backend with".to_string())?;
+        let game_url = config
+            .game_url(&game_id)
+            .ok_or_else(|| format!("Game '{}' not found in configuration", game_id))?;
+        let url = config::rewrite_url_host(game_url, base)?;
+
+        let client = crate::download::client_builder(&settings)?
+            .timeout(BACKEND_CHECK_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+        let response = client.get(&url).send().await.map_err(|e| {
+            format!(
+                "Backend {} is unreachable: {}",
+                config::redact_url(base),
+                e.without_url()
+            )
+        })?;
+        if !response.status().is_success() {
+            return Err(format!(
+                "Backend {} does not serve {}: HTTP {}",
+                config::redact_url(base),
+                game_id,
+                response.status()
+            ));
+        }
+    }
+
+    let mut settings = config::lock_settings(&settings);
+    let mut updated = settings.clone();
+    updated.backend_base = base;
+    config::save_settings(&updated)?;
+    *settings = updated;
+    Ok(())
+}
+
+/// Rename or drop Ruffle flags according to the config's `ruffle_flag_compat`
+/// map. Dropped flags take their value with them; every flag the launcher
+/// passes takes one.
+fn apply_flag_compat(args: Vec<OsString>, compat: &HashMap<String, String>) -> Vec<OsString> {
+    if compat.is_empty() {
+        return args;
+    }
+
+    let mut result = Vec::with_capacity(args.len());
+    let mut args = args.into_iter().peekable();
+    while let Some(arg) = args.next() {
+        match arg.to_str().and_then(|flag| compat.get(flag)) {
+            Some(replacement) if replacement.is_empty() => {
+                // Skip the value too, unless the next argument is another flag
+                if args
+                    .peek()
+                    .is_some_and(|next| !next.to_string_lossy().starts_with("--"))
+                {
+                    args.next();
+                }
+            }
+            Some(replacement) => result.push(OsString::from(replacement)),
+            None => result.push(arg),
+        }
+    }
+    result
+}
+
+/// The URL of the directory a game URL points into (its filename removed)
+fn base_url(game_url: &str) -> &str {
+    match game_url.rfind('/') {
+        Some(idx) => &game_url[..=idx],
+        None => game_url,
+    }
+}
+
+/// Ruffle flags for running a game outside the launcher, as in a portable
+/// bundle: the same spoofed URL, display and load flags as a launch
+pub fn portable_ruffle_args(
+    game_id: &str,
+    config: &AppConfig,
+    settings: &Settings,
+) -> Result<Vec<String>, String> {
+    let game_url = config
+        .resolve_game_url(game_id, settings)
+        .ok_or_else(|| format!("Game '{}' not found in configuration", game_id))?;
+    let mut args = ruffle_args(settings, &game_url, base_url(&game_url));
+    if let Some(entry) = config.games.get(game_id) {
+        args.extend(entry.ruffle_load_behavior.iter().cloned());
+    }
+    Ok(args)
+}
+
+/// Build the Ruffle command-line flags that follow the SWF path
+fn ruffle_args(settings: &Settings, game_url: &str, base_url: &str) -> Vec<String> {
+    let mut args = vec![
+        "--spoof-url".to_string(),
+        game_url.to_string(),
+        "--base".to_string(),
+        base_url.to_string(),
+    ];
+
+    if let Some(scale) = settings
+        .ruffle_scale
+        .as_deref()
+        .and_then(config::ruffle_scale_arg)
+    {
+        args.push("--scale".to_string());
+        args.push(scale.to_string());
+    }
+
+    if let Some(width) = settings.ruffle_width {
+        args.push("--width".to_string());
+        args.push(width.to_string());
+    }
+
+    if let Some(height) = settings.ruffle_height {
+        args.push("--height".to_string());
+        args.push(height.to_string());
+    }
+
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unicode_paths() -> (PathBuf, PathBuf) {
+        let root = Path::new("/home/zoë doe/PTD Launcher");
+        (
+            root.join("ü").join("ruffle"),
+            root.join("ü").join("ptd1.swf"),
+        )
+    }
+
+    #[test]
+    fn sol_name_reads_the_shared_object_name() {
+        let mut bytes = vec![0x00, 0xBF, 0, 0, 0, 0];
+        bytes.extend_from_slice(b"TCSO\x00\x04\x00\x00\x00\x00");
+        bytes.extend_from_slice(&[0, 8]);
+        bytes.extend_from_slice(b"ptd1save");
+        bytes.extend_from_slice(&[0, 0, 0, 3]);
+
+        assert!(is_sol(&bytes));
+        assert_eq!(sol_name(&bytes).as_deref(), Some("ptd1save"));
+        assert_eq!(sol_name(&bytes[..20]), None);
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    #[test]
+    fn flash_command_passes_game_path_as_one_argument() {
+        let (player_path, game_path) = unicode_paths();
+        let cmd = player_command_for(
+            "PTD1",
+            false,
+            &player_path,
+            &game_path,
+            "https://ptd.onl/ptd1-latest.swf",
+            &AppConfig::default(),
+            &Settings::default(),
+        )
+        .unwrap();
+
+        assert_eq!(cmd.get_program(), player_path.as_os_str());
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), [game_path.as_os_str()]);
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn flash_command_runs_the_bundle_executable() {
+        let root = std::env::temp_dir().join(format!("ptd test ü {}", std::process::id()));
+        let app = root.join("Flash Player.app");
+        let macos_dir = app.join("Contents").join("MacOS");
+        fs::create_dir_all(&macos_dir).unwrap();
+        fs::write(
+            app.join("Contents").join("Info.plist"),
+            "<dict><key>CFBundleExecutable</key><string>Flash Player Debugger</string></dict>",
+        )
+        .unwrap();
+        let game_path = root.join("ptd1.swf");
+
+        let cmd = player_command_for(
+            "PTD1",
+            false,
+            &app,
+            &game_path,
+            "https://ptd.onl/ptd1-latest.swf",
+            &AppConfig::default(),
+            &Settings::default(),
+        );
+        let _ = fs::remove_dir_all(&root);
+        let cmd = cmd.unwrap();
+
+        assert_eq!(
+            cmd.get_program(),
+            macos_dir.join("Flash Player Debugger").as_os_str()
+        );
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), [game_path.as_os_str()]);
+    }
+
+    #[test]
+    fn ruffle_command_passes_game_path_as_one_argument() {
+        let (player_path, game_path) = unicode_paths();
+        let cmd = player_command_for(
+            "PTD1",
+            true,
+            &player_path,
+            &game_path,
+            "https://ptd.onl/ptd1-latest.swf",
+            &AppConfig::default(),
+            &Settings::default(),
+        )
+        .unwrap();
+
+        assert_eq!(cmd.get_program(), player_path.as_os_str());
+        let args: Vec<_> = cmd.get_args().collect();
+        assert_eq!(args.first(), Some(&game_path.as_os_str()));
+        assert!(args[1..]
+            .iter()
+            .all(|arg| !arg.to_string_lossy().contains("PTD Launcher")));
+    }
+}
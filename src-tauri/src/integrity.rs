@@ -0,0 +1,201 @@
+//! SHA-256 records of installed files, so installs can be checked for
+//! corruption without re-hashing files that haven't changed.
+
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use crate::config::{self, FileHash, GameVersions};
+use crate::logger;
+
+/// Stream a file through SHA-256, returning the hash as lowercase hex
+pub fn hash_file(path: &Path) -> Result<String, String> {
+    let mut file =
+        fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// [`hash_file`] on a blocking thread, for async commands
+pub async fn hash_file_async(path: PathBuf) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || hash_file(&path))
+        .await
+        .map_err(|e| format!("Failed to hash file: {}", e))?
+}
+
+/// Size and modification time (milliseconds since the Unix epoch) of a file
+fn file_stamp(path: &Path) -> Result<(u64, u64), String> {
+    let metadata =
+        fs::metadata(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let modified_ms = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    Ok((metadata.len(), modified_ms))
+}
+
+fn is_under(key: &str, root: &Path) -> bool {
+    Path::new(key).starts_with(root)
+}
+
+/// Hashes of the files under a path, ready to be [`record`]ed
+pub struct TreeHashes {
+    root: PathBuf,
+    files: Vec<(String, FileHash)>,
+}
+
+/// Hash `path`, a file or a directory tree. Files that can't be hashed are
+/// logged and left out rather than failing the install. This reads every
+/// file, so call it before locking the versions.
+pub fn hash_tree(path: &Path) -> TreeHashes {
+    let files = walkdir::WalkDir::new(path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|entry| {
+            let file = entry.path();
+            let hash = file_stamp(file)
+                .and_then(|(size, modified_ms)| Ok((hash_file(file)?, size, modified_ms)));
+            match hash {
+                Ok((sha256, size, modified_ms)) => Some((
+                    file.to_string_lossy().into_owned(),
+                    FileHash {
+                        sha256,
+                        size,
+                        modified_ms,
+                    },
+                )),
+                Err(e) => {
+                    logger::log(&format!("Warning: not recording file hash: {}", e));
+                    None
+                }
+            }
+        })
+        .collect();
+    TreeHashes {
+        root: path.to_path_buf(),
+        files,
+    }
+}
+
+/// [`hash_tree`] on a blocking thread, for async commands
+pub async fn hash_tree_async(path: PathBuf) -> Result<TreeHashes, String> {
+    tauri::async_runtime::spawn_blocking(move || hash_tree(&path))
+        .await
+        .map_err(|e| format!("Failed to hash files: {}", e))
+}
+
+/// Record hashes from [`hash_tree`], replacing whatever was recorded under
+/// the same path before
+pub fn record(versions: &mut GameVersions, hashes: TreeHashes) {
+    forget(versions, &hashes.root);
+    versions.file_hashes.extend(hashes.files);
+}
+
+/// Drop recorded hashes for `path` and anything under it
+pub fn forget(versions: &mut GameVersions, path: &Path) {
+    versions.file_hashes.retain(|key, _| !is_under(key, path));
+}
+
+#[derive(Default, serde::Serialize)]
+pub struct VerifyReport {
+    /// Number of recorded files checked
+    pub checked: usize,
+    /// Number of files that were re-hashed
+    pub rehashed: usize,
+    /// Recorded files that no longer exist
+    pub missing: Vec<String>,
+    /// Files whose contents differ from the recorded hash
+    pub modified: Vec<String>,
+    /// Files that exist but could not be read
+    pub unreadable: Vec<String>,
+}
+
+/// Check installed files against the hashes recorded when they were
+/// installed. Files whose size and modification time still match are trusted
+/// without reading them; `deep` re-hashes every file regardless.
+#[tauri::command(async)]
+pub fn verify_installation(
+    deep: Option<bool>,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
+) -> Result<VerifyReport, String> {
+    let deep = deep.unwrap_or(false);
+    // Hash from a snapshot so installs aren't blocked while files are read
+    let mut snapshot = config::lock_versions(&versions).clone();
+    let mut report = VerifyReport::default();
+    let mut touched = Vec::new();
+
+    let mut paths: Vec<String> = snapshot.file_hashes.keys().cloned().collect();
+    paths.sort();
+    for key in paths {
+        report.checked += 1;
+        let path = Path::new(&key);
+        let Ok((size, modified_ms)) = file_stamp(path) else {
+            report.missing.push(key);
+            continue;
+        };
+
+        let Some(stored) = snapshot.file_hashes.get_mut(&key) else {
+            continue;
+        };
+        if size != stored.size {
+            report.modified.push(key);
+            continue;
+        }
+        if !deep && modified_ms == stored.modified_ms {
+            continue;
+        }
+
+        report.rehashed += 1;
+        let sha256 = match hash_file(path) {
+            Ok(sha256) => sha256,
+            Err(e) => {
+                crate::logger::log(&format!("Warning: {}", e));
+                report.unreadable.push(key);
+                continue;
+            }
+        };
+        if sha256 == stored.sha256 {
+            // Only the timestamp changed; remember it so the file isn't
+            // re-hashed next time
+            if stored.modified_ms != modified_ms {
+                touched.push((key, stored.sha256.clone(), modified_ms));
+            }
+        } else {
+            report.modified.push(key);
+        }
+    }
+
+    if !touched.is_empty() {
+        config::update_versions(&versions, |versions| {
+            for (key, sha256, modified_ms) in touched {
+                // Skip files reinstalled while verifying
+                if let Some(stored) = versions.file_hashes.get_mut(&key) {
+                    if stored.sha256 == sha256 {
+                        stored.modified_ms = modified_ms;
+                    }
+                }
+            }
+        })?;
+    }
+    Ok(report)
+}
@@ -1,13 +1,50 @@
+mod archive;
+mod breaker;
+mod bundle;
 mod config;
+mod diagnostics;
+mod disk;
+mod download;
+mod errors;
 mod flash;
 mod game;
+mod integrity;
+mod logger;
+mod process;
+mod queue;
 mod ruffle;
+mod tls;
+mod update;
 
 use config::{AppConfig, Settings};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use tauri::{Emitter, Manager};
+use tauri_plugin_opener::OpenerExt;
 
 fn load_bundled_config() -> Result<AppConfig, String> {
+    // A config the user edited (see `open_config_file`) wins, unless it
+    // fails the checks that keep game ids and URLs safe to use
+    if let Ok(user_path) = config::get_user_config_path() {
+        if user_path.exists() {
+            let config = config::load_config(&user_path)?;
+            let errors = config::validate_config(&config);
+            if errors.is_empty() {
+                // Keep the last config that loaded, so a bad edit can be undone
+                if let Err(e) = config::backup_user_config() {
+                    logger::log(&format!("Warning: failed to back up config.json: {}", e));
+                }
+                return Ok(config);
+            }
+            logger::log(&format!(
+                "Ignoring {}, using the bundled config:\n{}",
+                user_path.display(),
+                errors.join("\n")
+            ));
+        }
+    }
+
     // During development, load from resources folder
     let config_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
         .parent()
@@ -47,9 +84,12 @@ pub fn run() {
 
     // Load configuration
     let app_config = match load_bundled_config() {
-        Ok(config) => config,
+        Ok(config) => config.merge_bundled(&AppConfig::default()),
         Err(e) => {
-            eprintln!("Failed to load config: {}. Using default configuration.", e);
+            logger::log(&format!(
+                "Failed to load config: {}. Using default configuration.",
+                e
+            ));
             AppConfig::default()
         }
     };
@@ -57,11 +97,45 @@ pub fn run() {
     // Load settings
     let settings = config::load_settings().unwrap_or_default();
 
+    // Cache version.json; commands update it and write it back through
+    let versions = config::load_versions().unwrap_or_else(|e| {
+        logger::log(&format!("Failed to load versions: {}", e));
+        config::GameVersions::default()
+    });
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .manage(app_config)
         .manage(Mutex::new(settings))
+        .manage(Mutex::new(versions))
+        .manage(download::ActiveDownloads::default())
+        .manage(download::DownloadTraces::default())
+        .manage(breaker::HostBreakers::default())
+        .manage(queue::DownloadQueue::default())
+        .manage(process::RunningGames::default())
+        .on_window_event(|window, event| {
+            // Stop in-flight downloads so they don't leave .part files behind
+            if let tauri::WindowEvent::CloseRequested { .. } = event {
+                window.state::<download::ActiveDownloads>().cancel_all();
+            }
+        })
+        .setup(|app| {
+            if let Some(window) = app.get_webview_window("main") {
+                tauri::async_runtime::spawn(ruffle::auto_update(window.as_ref().window()));
+            }
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
+            // Download commands
+            download::cancel_download,
+            download::cancel_all_downloads,
+            download::download_with_trace,
+            download::get_data_usage,
+            download::reset_data_usage,
+            // Queue commands
+            queue::enqueue_download,
+            queue::get_queue,
+            queue::reorder_queue,
             // Flash commands
             flash::check_flash_installed,
             flash::get_flash_path,
@@ -70,14 +144,65 @@ pub fn run() {
             ruffle::check_ruffle_installed,
             ruffle::get_ruffle_path,
             ruffle::download_ruffle,
+            ruffle::check_ruffle_update,
+            ruffle::prune_ruffle_cache,
+            ruffle::list_ruffle_installs,
+            ruffle::set_active_ruffle,
+            ruffle::list_ruffle_storage,
+            ruffle::clear_ruffle_storage,
             // Game commands
+            game::get_game_metadata,
+            game::list_games_ordered,
+            game::get_save_info,
+            game::get_game_readiness,
+            bundle::export_playable_bundle,
+            game::export_save,
+            game::import_save,
+            game::get_game_icon,
             game::is_game_downloaded,
             game::get_game_path,
             game::download_game,
+            game::check_game_update,
+            game::compare_game_remote,
+            game::download_game_to,
+            game::rollback_game,
+            game::prune_games_dir,
+            game::import_games_folder,
             game::launch_game,
+            game::safe_launch,
+            game::relaunch_last,
+            game::launch_game_debug,
+            process::get_running_games,
+            game::test_download_speed,
+            game::set_backend,
+            // Update commands
+            update::update_all,
+            update::estimate_install_size,
+            // Diagnostics commands
+            diagnostics::run_diagnostics,
+            diagnostics::validate_player_paths,
+            diagnostics::detect_sandbox,
+            diagnostics::check_connectivity,
+            disk::check_low_disk,
+            diagnostics::test_player,
+            diagnostics::get_install_report,
+            diagnostics::get_version_info,
+            diagnostics::get_ruffle_log,
+            diagnostics::create_support_bundle,
+            integrity::verify_installation,
             // Settings commands
             get_settings,
+            check_settings,
             save_settings,
+            validate_config,
+            get_config_path,
+            open_config_file,
+            restore_previous_config,
+            get_default_player,
+            set_default_player,
+            get_app_state,
+            get_installed_players,
+            reinitialize,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -85,29 +210,303 @@ pub fn run() {
 
 #[tauri::command]
 fn get_settings(settings: tauri::State<'_, Mutex<Settings>>) -> Settings {
-    match settings.lock() {
-        Ok(s) => s.clone(),
-        Err(poisoned) => {
-            // Recover inner value if mutex was poisoned
-            poisoned.into_inner().clone()
-        }
-    }
+    config::lock_settings(&settings).clone()
 }
 
+/// Every problem [`Settings::validate`] finds in `new_settings`, by field, so
+/// the settings form can mark each one. Nothing is saved.
 #[tauri::command]
+fn check_settings(new_settings: Settings) -> Vec<config::ValidationError> {
+    new_settings.validate()
+}
+
+/// Validate and save settings. A custom player path that changed is checked
+/// first, so a broken path is rejected now rather than at the next launch:
+/// Ruffle is test-run, a Flash projector is checked on disk.
+#[tauri::command(async)]
 fn save_settings(
     new_settings: Settings,
     settings: tauri::State<'_, Mutex<Settings>>,
 ) -> Result<(), String> {
-    match settings.lock() {
-        Ok(mut s) => {
-            *s = new_settings.clone();
+    config::validate_settings(&new_settings)?;
+
+    let current = config::lock_settings(&settings).clone();
+    let custom_paths = [
+        (
+            "flash",
+            &new_settings.flash_player_path,
+            &current.flash_player_path,
+        ),
+        ("ruffle", &new_settings.ruffle_path, &current.ruffle_path),
+    ];
+    for (kind, new_path, current_path) in custom_paths {
+        let Some(path) = new_path.as_deref().filter(|p| !p.trim().is_empty()) else {
+            continue;
+        };
+        if new_path == current_path {
+            continue;
         }
-        Err(poisoned) => {
-            let mut guard = poisoned.into_inner();
-            *guard = new_settings.clone();
+        // A Flash projector opens a window when started, so it is only
+        // checked on disk here; `test_player` runs it on request
+        let error = if kind == "flash" {
+            flash::check_flash_path(Path::new(path), &new_settings).err()
+        } else {
+            diagnostics::test_player_path(Path::new(path), kind, &new_settings)?.error
+        };
+        if let Some(e) = error {
+            return Err(format!("Custom {} path {} doesn't work: {}", kind, path, e));
         }
     }
 
+    *config::lock_settings(&settings) = new_settings.clone();
+
     config::save_settings(&new_settings)
 }
+
+/// Summary of a config that passed [`validate_config`]
+#[derive(serde::Serialize)]
+struct ConfigSummary {
+    game_count: usize,
+    /// Download URL by game id
+    games: BTreeMap<String, String>,
+}
+
+/// Check a config.json blob without applying it. Returns a summary when it is
+/// valid, or every problem found: a JSON error with the offending line, or the
+/// URL and file name checks from [`config::validate_config`].
+#[tauri::command]
+fn validate_config(json: String) -> Result<ConfigSummary, Vec<String>> {
+    let app_config = serde_json::from_str::<AppConfig>(&json)
+        .map(AppConfig::migrate_game_urls)
+        .map_err(|e| {
+            let mut error = format!("Invalid config: {}", e);
+            if let Some(line) = json.lines().nth(e.line().saturating_sub(1)) {
+                error.push_str(&format!("\n{:>5} | {}", e.line(), line.trim_end()));
+            }
+            vec![error]
+        })?;
+
+    let errors = config::validate_config(&app_config);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    Ok(ConfigSummary {
+        game_count: app_config.games.len(),
+        games: app_config
+            .games
+            .into_iter()
+            .map(|(id, game)| (id, game.url))
+            .collect(),
+    })
+}
+
+/// Path of the config.json the launcher loaded, or `None` when it is running
+/// on the built-in default config
+#[tauri::command]
+fn get_config_path(app_config: tauri::State<'_, AppConfig>) -> Option<String> {
+    app_config
+        .source_path
+        .as_ref()
+        .map(|p| p.to_string_lossy().into_owned())
+}
+
+/// Open the active config.json in the system's default editor. When running
+/// on the built-in default, it is first written to the user config path so
+/// there is a file to edit; edits there apply on the next start. Returns the
+/// opened path.
+#[tauri::command]
+fn open_config_file(
+    app: tauri::AppHandle,
+    app_config: tauri::State<'_, AppConfig>,
+) -> Result<String, String> {
+    let path = match &app_config.source_path {
+        Some(path) => path.clone(),
+        None => {
+            let path = config::get_user_config_path()?;
+            // A user config that exists but failed to load is left for the
+            // user to fix rather than overwritten
+            if !path.exists() {
+                let content = serde_json::to_string_pretty(&*app_config)
+                    .map_err(|e| format!("Failed to serialize config: {}", e))?;
+                config::write_user_config(&content)?;
+            }
+            path
+        }
+    };
+
+    app.opener()
+        .open_path(path.to_string_lossy(), None::<&str>)
+        .map_err(|e| format!("Failed to open config.json: {}", e))?;
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Replace the user config.json with the version it had before it was last
+/// written, keeping the current one as a backup. Applies on the next start.
+/// Returns the config path.
+#[tauri::command]
+fn restore_previous_config() -> Result<String, String> {
+    config::restore_user_config().map(|p| p.to_string_lossy().into_owned())
+}
+
+/// The player used when a launch doesn't pick one: "flash" or "ruffle"
+#[tauri::command]
+fn get_default_player(settings: tauri::State<'_, Mutex<Settings>>) -> String {
+    if config::lock_settings(&settings).use_ruffle.unwrap_or(false) {
+        "ruffle"
+    } else {
+        "flash"
+    }
+    .to_string()
+}
+
+/// Set the default player ("flash" or "ruffle") without rewriting the other
+/// settings, then emit `settings-changed` with the updated settings
+#[tauri::command]
+fn set_default_player(
+    app: tauri::AppHandle,
+    player: String,
+    settings: tauri::State<'_, Mutex<Settings>>,
+) -> Result<(), String> {
+    let use_ruffle = match player.as_str() {
+        "ruffle" => true,
+        "flash" => false,
+        other => {
+            return Err(format!(
+                "Unknown player '{}'. Expected 'flash' or 'ruffle'",
+                other
+            ))
+        }
+    };
+
+    let updated = {
+        let mut settings = config::lock_settings(&settings);
+        let mut updated = settings.clone();
+        updated.use_ruffle = Some(use_ruffle);
+        config::save_settings(&updated)?;
+        *settings = updated.clone();
+        updated
+    };
+
+    let _ = app.emit("settings-changed", updated);
+    Ok(())
+}
+
+/// Which players are installed
+#[derive(serde::Serialize)]
+struct InstalledPlayers {
+    flash: bool,
+    ruffle: bool,
+}
+
+fn installed_players(app_config: &AppConfig, settings: &Settings) -> InstalledPlayers {
+    InstalledPlayers {
+        flash: config::get_flash_player_path(app_config, settings)
+            .map(|p| p.exists())
+            .unwrap_or(false),
+        ruffle: config::get_ruffle_path(app_config, settings)
+            .map(|p| p.exists())
+            .unwrap_or(false),
+    }
+}
+
+/// Check both players in one round-trip, so the UI can decide whether to offer
+/// a player toggle or show that no player is installed
+#[tauri::command]
+fn get_installed_players(
+    app_config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+) -> InstalledPlayers {
+    let settings = config::lock_settings(&settings);
+    installed_players(&app_config, &settings)
+}
+
+/// A downloaded game and the version recorded for it
+#[derive(serde::Serialize)]
+struct InstalledGame {
+    id: String,
+    path: String,
+    version: Option<String>,
+    /// When the installed build was downloaded, as an RFC 3339 timestamp
+    installed_at: Option<String>,
+    play_count: u64,
+}
+
+/// Everything the home screen needs, gathered in one round-trip
+#[derive(serde::Serialize)]
+struct AppState {
+    settings: Settings,
+    /// "flash" or "ruffle"
+    active_player: String,
+    flash_installed: bool,
+    ruffle_installed: bool,
+    flash_version: String,
+    ruffle_version: String,
+    installed_games: Vec<InstalledGame>,
+    /// Always None here so this call stays offline; the UI checks for updates separately
+    ruffle_update_available: Option<bool>,
+    /// The active player is installed and at least one game is downloaded
+    onboarding_complete: bool,
+}
+
+#[tauri::command]
+fn get_app_state(
+    app_config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+    versions: tauri::State<'_, Mutex<config::GameVersions>>,
+) -> AppState {
+    let settings = config::lock_settings(&settings).clone();
+
+    let InstalledPlayers {
+        flash: flash_installed,
+        ruffle: ruffle_installed,
+    } = installed_players(&app_config, &settings);
+    let use_ruffle = settings.use_ruffle.unwrap_or(false);
+
+    let versions = config::lock_versions(&versions).clone();
+
+    let installed_games: Vec<InstalledGame> = app_config
+        .game_ids()
+        .into_iter()
+        .filter_map(|id| {
+            let path = game::find_game_path(id).ok().flatten()?;
+            Some(InstalledGame {
+                id: id.clone(),
+                path: path.to_string_lossy().into_owned(),
+                version: versions.games.get(id).map(|v| v.version.clone()),
+                installed_at: versions.games.get(id).and_then(|v| v.installed_at.clone()),
+                play_count: versions.play_counts.get(id).copied().unwrap_or(0),
+            })
+        })
+        .collect();
+
+    let player_installed = if use_ruffle {
+        ruffle_installed
+    } else {
+        flash_installed
+    };
+
+    AppState {
+        active_player: if use_ruffle { "ruffle" } else { "flash" }.to_string(),
+        flash_installed,
+        ruffle_installed,
+        flash_version: versions.flash_player,
+        ruffle_version: versions.ruffle,
+        onboarding_complete: player_installed && !installed_games.is_empty(),
+        installed_games,
+        ruffle_update_available: None,
+        settings,
+    }
+}
+
+/// Re-create the app data directories, e.g. after they were deleted while the
+/// launcher was running. Returns the directories that had to be created.
+#[tauri::command]
+fn reinitialize() -> Result<Vec<String>, String> {
+    config::init_config().map(|created| {
+        created
+            .into_iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect()
+    })
+}
@@ -0,0 +1,68 @@
+//! Launcher log written to `launcher.log` in the app directory, so support can
+//! see what happened on a user's machine.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+
+use crate::config;
+
+/// Rotate the log to `launcher.log.old` once it grows past this size
+const MAX_LOG_SIZE: u64 = 1024 * 1024; // 1 MB
+
+/// Get the launcher log path
+pub fn get_log_path() -> Result<PathBuf, String> {
+    config::get_app_dir().map(|p| p.join("launcher.log"))
+}
+
+/// Get the path of the log Ruffle's output goes to when `ruffle_log_level`
+/// is set
+pub fn get_ruffle_log_path() -> Result<PathBuf, String> {
+    config::get_app_dir().map(|p| p.join("ruffle.log"))
+}
+
+/// Open the Ruffle log for appending the output of a launch of `game_id`,
+/// rotating it like the launcher log. `None` if it can't be opened.
+pub fn open_ruffle_log(game_id: &str) -> Option<fs::File> {
+    let path = get_ruffle_log_path().ok()?;
+    if fs::metadata(&path).is_ok_and(|m| m.len() > MAX_LOG_SIZE) {
+        let _ = fs::rename(&path, path.with_extension("log.old"));
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .ok()?;
+    let _ = writeln!(
+        file,
+        "=== {} launched at {} ===",
+        game_id,
+        chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+    );
+    Some(file)
+}
+
+/// Append a timestamped line to the launcher log and echo it to stderr.
+/// Failures to write the log are ignored so logging never breaks a command.
+pub fn log(message: &str) {
+    eprintln!("{}", message);
+
+    let Ok(path) = get_log_path() else {
+        return;
+    };
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if fs::metadata(&path).is_ok_and(|m| m.len() > MAX_LOG_SIZE) {
+        let _ = fs::rename(&path, path.with_extension("log.old"));
+    }
+
+    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&path) {
+        let _ = writeln!(
+            file,
+            "[{}] {}",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+            message
+        );
+    }
+}
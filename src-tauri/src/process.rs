@@ -0,0 +1,181 @@
+//! Tracking of launched game processes, so the same game isn't started twice.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::process::{Child, ChildStderr, Command, ExitStatus};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::errors;
+
+/// Player processes started by the launcher, keyed by game id
+#[derive(Default)]
+pub struct RunningGames(Mutex<Games>);
+
+/// Identifies one launch of a player, so a relaunch of the same game isn't
+/// mistaken for the earlier one
+pub type LaunchId = u64;
+
+static NEXT_LAUNCH_ID: AtomicU64 = AtomicU64::new(1);
+
+struct Tracked {
+    launch: LaunchId,
+    child: Child,
+}
+
+/// How many exit statuses of players no longer tracked are remembered
+const MAX_EXITED: usize = 32;
+
+#[derive(Default)]
+struct Games {
+    running: HashMap<String, Tracked>,
+    /// How players that exited on their own ended, kept after they stop
+    /// being tracked so a crash watcher can still find out. Oldest first.
+    exited: VecDeque<(LaunchId, ExitStatus)>,
+}
+
+impl Games {
+    /// Forget players that have exited, remembering their exit status
+    fn prune(&mut self) {
+        let exited = &mut self.exited;
+        self.running
+            .retain(|_, tracked| match tracked.child.try_wait() {
+                Ok(Some(status)) => {
+                    if exited.len() == MAX_EXITED {
+                        exited.pop_front();
+                    }
+                    exited.push_back((tracked.launch, status));
+                    false
+                }
+                Ok(None) => true,
+                Err(_) => false,
+            });
+    }
+
+    fn track(&mut self, game_id: &str, child: Child) -> LaunchId {
+        let launch = NEXT_LAUNCH_ID.fetch_add(1, Ordering::Relaxed);
+        self.running
+            .insert(game_id.to_string(), Tracked { launch, child });
+        launch
+    }
+}
+
+/// How often [`RunningGames::wait_for_exit`] checks the player
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+impl RunningGames {
+    fn lock(&self) -> MutexGuard<'_, Games> {
+        self.0.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Track a game launched outside of [`RunningGames::spawn`]
+    pub fn insert(&self, game_id: &str, child: Child) -> LaunchId {
+        self.lock().track(game_id, child)
+    }
+
+    /// Take the piped stderr of a launch's player
+    pub fn take_stderr(&self, launch: LaunchId) -> Option<ChildStderr> {
+        self.lock()
+            .running
+            .values_mut()
+            .find(|tracked| tracked.launch == launch)?
+            .child
+            .stderr
+            .take()
+    }
+
+    /// Exit status of a launch's player, or `None` while it is still running,
+    /// if it was stopped by the launcher or if it is no longer remembered
+    pub fn exit_status(&self, launch: LaunchId) -> Option<ExitStatus> {
+        let mut games = self.lock();
+        if let Some(tracked) = games
+            .running
+            .values_mut()
+            .find(|tracked| tracked.launch == launch)
+        {
+            return tracked.child.try_wait().ok().flatten();
+        }
+        games
+            .exited
+            .iter()
+            .find(|(id, _)| *id == launch)
+            .map(|(_, status)| *status)
+    }
+
+    /// Wait up to `timeout` for a game's player to exit and return its
+    /// status. A player whose output just closed may not have been reaped yet.
+    pub fn wait_for_exit(&self, launch: LaunchId, timeout: Duration) -> Option<ExitStatus> {
+        let started = Instant::now();
+        loop {
+            if let Some(status) = self.exit_status(launch) {
+                return Some(status);
+            }
+            if started.elapsed() >= timeout {
+                return None;
+            }
+            std::thread::sleep(EXIT_POLL_INTERVAL);
+        }
+    }
+
+    /// Ids of games whose player is still running, sorted
+    pub fn running_ids(&self) -> Vec<String> {
+        let mut games = self.lock();
+        games.prune();
+        let mut ids: Vec<String> = games.running.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Refuse to launch a game that is already running, unless `force` is set,
+    /// in which case the running instance is killed first. Two instances of
+    /// one game write to the same save file and can corrupt it.
+    pub fn ensure_not_running(&self, game_id: &str, force: bool) -> Result<(), String> {
+        ensure_not_running(&mut self.lock(), game_id, force)
+    }
+
+    /// Check the game isn't running and spawn it, holding the lock throughout
+    /// so two quick launches can't both get through
+    pub fn spawn(&self, game_id: &str, force: bool, cmd: &mut Command) -> Result<LaunchId, String> {
+        let mut games = self.lock();
+        ensure_not_running(&mut games, game_id, force)?;
+        let child = cmd.spawn().map_err(|e| {
+            errors::spawn_error("Failed to launch game", Path::new(cmd.get_program()), &e)
+        })?;
+        Ok(games.track(game_id, child))
+    }
+}
+
+/// Whether a tracked process is still alive; exited ones are forgotten
+fn is_alive(games: &mut Games, game_id: &str) -> bool {
+    games.prune();
+    games.running.contains_key(game_id)
+}
+
+fn ensure_not_running(games: &mut Games, game_id: &str, force: bool) -> Result<(), String> {
+    if !is_alive(games, game_id) {
+        return Ok(());
+    }
+    if !force {
+        return Err(format!(
+            "'{}' is already running. Close it before launching it again.",
+            game_id
+        ));
+    }
+
+    // A player stopped here didn't crash, so its status isn't recorded
+    if let Some(Tracked { mut child, .. }) = games.running.remove(game_id) {
+        child
+            .kill()
+            .map_err(|e| format!("Failed to stop running '{}': {}", game_id, e))?;
+        // Reap the process so it doesn't linger as a zombie
+        let _ = child.wait();
+    }
+    Ok(())
+}
+
+/// Ids of the games currently running
+#[tauri::command]
+pub fn get_running_games(running: tauri::State<'_, RunningGames>) -> Vec<String> {
+    running.running_ids()
+}
@@ -0,0 +1,273 @@
+//! Download queue that runs flash, ruffle and game downloads through a single
+//! worker instead of as independent commands.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use tauri::{Manager, Window};
+use tokio::sync::Notify;
+use tokio::task::JoinSet;
+
+use crate::config::AppConfig;
+use crate::{flash, game, logger, ruffle};
+
+/// Queue item for the flash player, matching its `download-progress` item name
+pub const FLASH_ITEM: &str = "flash_player";
+/// Queue item for ruffle, matching its `download-progress` item name
+pub const RUFFLE_ITEM: &str = "ruffle";
+
+/// How many queued downloads may run at the same time
+const MAX_CONCURRENT_DOWNLOADS: usize = 2;
+
+/// How many finished downloads stay listed; older ones are dropped
+const MAX_FINISHED_DOWNLOADS: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueStatus {
+    Pending,
+    Active,
+    Done,
+    Failed,
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct QueuedDownload {
+    /// "flash_player", "ruffle" or a game id
+    pub item: String,
+    pub status: QueueStatus,
+    /// Set when the download failed
+    pub error: Option<String>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    items: VecDeque<QueuedDownload>,
+    worker_running: bool,
+}
+
+/// Downloads waiting for, running in, or finished by the queue worker
+#[derive(Default)]
+pub struct DownloadQueue {
+    state: Mutex<QueueState>,
+    /// Wakes the worker to fill a free slot when something is queued
+    wake: Notify,
+}
+
+impl DownloadQueue {
+    fn lock(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.state.lock().unwrap_or_else(|p| p.into_inner())
+    }
+
+    /// Mark the next pending item active and return it
+    fn start_next(&self) -> Option<String> {
+        let mut state = self.lock();
+        let next = state
+            .items
+            .iter_mut()
+            .find(|d| d.status == QueueStatus::Pending)?;
+        next.status = QueueStatus::Active;
+        Some(next.item.clone())
+    }
+
+    fn complete(&self, item: &str, result: Result<String, String>) {
+        let mut state = self.lock();
+        if let Some(download) = state.items.iter_mut().find(|d| d.item == item) {
+            match result {
+                Ok(_) => download.status = QueueStatus::Done,
+                Err(e) => {
+                    download.status = QueueStatus::Failed;
+                    download.error = Some(e);
+                }
+            }
+        }
+
+        let is_finished =
+            |d: &QueuedDownload| matches!(d.status, QueueStatus::Done | QueueStatus::Failed);
+        let mut finished = state.items.iter().filter(|d| is_finished(d)).count();
+        while finished > MAX_FINISHED_DOWNLOADS {
+            if let Some(oldest) = state.items.iter().position(is_finished) {
+                state.items.remove(oldest);
+            }
+            finished -= 1;
+        }
+    }
+
+    /// Stop the worker unless something was queued since it last looked.
+    /// Returns true if the worker should exit.
+    fn stop_if_idle(&self) -> bool {
+        let mut state = self.lock();
+        if state.items.iter().any(|d| d.status == QueueStatus::Pending) {
+            return false;
+        }
+        state.worker_running = false;
+        true
+    }
+}
+
+/// Add a download to the end of the queue, starting the worker if it's idle.
+/// Items that already finished are queued again; pending or active items are
+/// rejected.
+#[tauri::command]
+pub fn enqueue_download(
+    window: Window,
+    item: String,
+    config: tauri::State<'_, AppConfig>,
+    queue: tauri::State<'_, DownloadQueue>,
+) -> Result<Vec<QueuedDownload>, String> {
+    check_item(&item, &config)?;
+
+    let mut state = queue.lock();
+    if let Some(pos) = state.items.iter().position(|d| d.item == item) {
+        match state.items[pos].status {
+            QueueStatus::Pending | QueueStatus::Active => {
+                return Err(format!("'{}' is already queued", item));
+            }
+            QueueStatus::Done | QueueStatus::Failed => {
+                state.items.remove(pos);
+            }
+        }
+    }
+    state.items.push_back(QueuedDownload {
+        item,
+        status: QueueStatus::Pending,
+        error: None,
+    });
+
+    if state.worker_running {
+        queue.wake.notify_one();
+    } else {
+        state.worker_running = true;
+        tauri::async_runtime::spawn(run_worker(window));
+    }
+
+    Ok(state.items.iter().cloned().collect())
+}
+
+/// Reject items that aren't "flash_player", "ruffle" or a known game id
+pub fn check_item(item: &str, config: &AppConfig) -> Result<(), String> {
+    if item != FLASH_ITEM && item != RUFFLE_ITEM && !config.games.contains_key(item) {
+        return Err(format!("Unknown download '{}'", item));
+    }
+    Ok(())
+}
+
+/// The queue in order, including active and finished downloads
+#[tauri::command]
+pub fn get_queue(queue: tauri::State<'_, DownloadQueue>) -> Vec<QueuedDownload> {
+    queue.lock().items.iter().cloned().collect()
+}
+
+/// Reorder the pending downloads. Items in `new_order` are moved to the front
+/// of the pending downloads in that order; pending items not listed keep their
+/// relative order after them. Active and finished downloads don't move.
+#[tauri::command]
+pub fn reorder_queue(
+    new_order: Vec<String>,
+    queue: tauri::State<'_, DownloadQueue>,
+) -> Result<Vec<QueuedDownload>, String> {
+    let mut state = queue.lock();
+
+    let mut pending: Vec<QueuedDownload> = state
+        .items
+        .iter()
+        .filter(|d| d.status == QueueStatus::Pending)
+        .cloned()
+        .collect();
+
+    let mut reordered = Vec::with_capacity(pending.len());
+    for item in &new_order {
+        let pos = pending
+            .iter()
+            .position(|d| &d.item == item)
+            .ok_or_else(|| format!("'{}' is not waiting in the queue", item))?;
+        reordered.push(pending.remove(pos));
+    }
+    reordered.extend(pending);
+
+    // Put the pending items back into the slots pending items occupied
+    let mut reordered = reordered.into_iter();
+    for download in state.items.iter_mut() {
+        if download.status == QueueStatus::Pending {
+            if let Some(next) = reordered.next() {
+                *download = next;
+            }
+        }
+    }
+
+    Ok(state.items.iter().cloned().collect())
+}
+
+async fn run_worker(window: Window) {
+    let queue = window.state::<DownloadQueue>();
+    let mut running = JoinSet::new();
+
+    loop {
+        while running.len() < MAX_CONCURRENT_DOWNLOADS {
+            match queue.start_next() {
+                Some(item) => {
+                    running.spawn(run_download(window.clone(), item));
+                }
+                None => break,
+            }
+        }
+
+        if running.is_empty() {
+            if queue.stop_if_idle() {
+                break;
+            }
+            continue;
+        }
+
+        // Refill as soon as either a download finishes or one is queued
+        tokio::select! {
+            joined = running.join_next() => match joined {
+                Some(Ok((item, result))) => queue.complete(&item, result),
+                Some(Err(e)) => logger::log(&format!("Queued download task failed: {}", e)),
+                None => {}
+            },
+            _ = queue.wake.notified() => {}
+        }
+    }
+}
+
+async fn run_download(window: Window, item: String) -> (String, Result<String, String>) {
+    let result = download_item(window, &item).await;
+    (item, result)
+}
+
+/// Download one item with its default install location, returning the
+/// installed path
+pub async fn download_item(window: Window, item: &str) -> Result<String, String> {
+    match item {
+        FLASH_ITEM => {
+            flash::download_flash(
+                window.clone(),
+                None,
+                window.state(),
+                window.state(),
+                window.state(),
+            )
+            .await
+        }
+        RUFFLE_ITEM => {
+            ruffle::download_ruffle(
+                window.clone(),
+                None,
+                window.state(),
+                window.state(),
+                window.state(),
+            )
+            .await
+        }
+        game_id => {
+            game::download_game(
+                window.clone(),
+                game_id.to_string(),
+                window.state(),
+                window.state(),
+                window.state(),
+            )
+            .await
+        }
+    }
+}
@@ -1,26 +1,18 @@
-use crate::config::{self, AppConfig, Settings};
+use crate::archive;
+use crate::config::{self, AppConfig, GameVersions, Settings};
+use crate::download::{self, download_file_with_progress, DownloadPhase, DownloadProgress};
+use crate::{errors, integrity, logger};
+use std::cmp::Ordering;
 use std::fs;
-use std::io::Write;
-use std::path::PathBuf;
-use tauri::{Emitter, Window};
-
-#[derive(Clone, serde::Serialize)]
-pub struct DownloadProgress {
-    pub item: String,
-    pub progress: u32,
-    pub downloaded: u64,
-    pub total: u64,
-    pub status: String,
-}
-
 use std::sync::Mutex;
+use tauri::{Emitter, Manager, Window};
 
 #[tauri::command]
 pub fn check_ruffle_installed(
     config: tauri::State<'_, AppConfig>,
     settings: tauri::State<'_, Mutex<Settings>>,
 ) -> bool {
-    let settings = settings.lock().unwrap();
+    let settings = config::lock_settings(&settings);
     match config::get_ruffle_path(&config, &settings) {
         Ok(path) => path.exists(),
         Err(_) => false,
@@ -32,13 +24,514 @@ pub fn get_ruffle_path(
     config: tauri::State<'_, AppConfig>,
     settings: tauri::State<'_, Mutex<Settings>>,
 ) -> Result<String, String> {
-    let settings = settings.lock().unwrap();
+    let settings = config::lock_settings(&settings);
     let path = config::get_ruffle_path(&config, &settings)?;
     path.to_str()
         .map(|s| s.to_string())
         .ok_or_else(|| "Invalid path".to_string())
 }
 
+/// A Ruffle release version parsed from its tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuffleVersion {
+    /// `nightly-YYYY-MM-DD`
+    Nightly(chrono::NaiveDate),
+    /// `vX.Y.Z` (the `v` is optional)
+    Stable(u32, u32, u32),
+}
+
+impl RuffleVersion {
+    /// The release tag for this version
+    pub fn tag(&self) -> String {
+        match self {
+            Self::Nightly(date) => format!("nightly-{}", date.format("%Y-%m-%d")),
+            Self::Stable(major, minor, patch) => format!("v{}.{}.{}", major, minor, patch),
+        }
+    }
+}
+
+/// Nightlies order by date and stable releases by version number. A nightly
+/// and a stable release aren't comparable.
+impl PartialOrd for RuffleVersion {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match (self, other) {
+            (Self::Nightly(a), Self::Nightly(b)) => a.partial_cmp(b),
+            (Self::Stable(a, b, c), Self::Stable(x, y, z)) => (a, b, c).partial_cmp(&(x, y, z)),
+            _ => None,
+        }
+    }
+}
+
+/// Parse a Ruffle release tag, either `nightly-YYYY-MM-DD` or `vX.Y.Z`
+pub fn parse_ruffle_version(tag: &str) -> Option<RuffleVersion> {
+    let tag = tag.trim();
+    if let Some(date) = tag.strip_prefix("nightly-") {
+        return chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .ok()
+            .map(RuffleVersion::Nightly);
+    }
+
+    let mut parts = tag.strip_prefix('v').unwrap_or(tag).split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next()?.parse().ok()?;
+    let patch = parts.next()?.parse().ok()?;
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(RuffleVersion::Stable(major, minor, patch))
+}
+
+/// Version of a Ruffle archive from its asset name, e.g.
+/// `ruffle-nightly-2026_02_09-linux-x86_64.tar.gz`
+fn archive_version(name: &str) -> Option<RuffleVersion> {
+    let parts: Vec<&str> = name.split('-').collect();
+    parts.iter().enumerate().find_map(|(i, part)| {
+        if *part == "nightly" {
+            let date = parts.get(i + 1)?.replace('_', "-");
+            parse_ruffle_version(&format!("nightly-{}", date))
+        } else if part.starts_with('v') {
+            parse_ruffle_version(part)
+        } else {
+            None
+        }
+    })
+}
+
+/// Move a downloaded archive into the Ruffle cache directory
+fn cache_archive(download_path: &std::path::Path, archive_name: &str) -> Result<(), String> {
+    let cache_dir = config::get_ruffle_cache_dir()?;
+    fs::create_dir_all(&cache_dir)
+        .map_err(|e| errors::io_error("Failed to create Ruffle cache directory", &e))?;
+    let cached = cache_dir.join(archive_name);
+    // Renaming fails across filesystems, e.g. with a custom install directory
+    if fs::rename(download_path, &cached).is_err() {
+        fs::copy(download_path, &cached)
+            .map_err(|e| errors::io_error("Failed to copy Ruffle archive", &e))?;
+    }
+    Ok(())
+}
+
+/// Delete cached Ruffle archives except the newest `keep`, ordered by nightly
+/// date and then by download time. The archive of the installed version is
+/// never deleted. Returns the number of bytes freed.
+#[tauri::command]
+pub fn prune_ruffle_cache(
+    keep: usize,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
+) -> Result<u64, String> {
+    let cache_dir = config::get_ruffle_cache_dir()?;
+    if !cache_dir.exists() {
+        return Ok(0);
+    }
+    let installed = parse_ruffle_version(&config::lock_versions(&versions).ruffle);
+
+    let entries = fs::read_dir(&cache_dir)
+        .map_err(|e| format!("Failed to read Ruffle cache directory: {}", e))?;
+    let mut archives: Vec<_> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok().filter(|m| m.is_file())?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let version = archive_version(&name);
+            let date = match version {
+                Some(RuffleVersion::Nightly(date)) => Some(date),
+                _ => None,
+            };
+            let modified = metadata.modified().ok();
+            Some((entry.path(), version, date, modified, metadata.len()))
+        })
+        .collect();
+    archives.sort_by_key(|(_, _, date, modified, _)| std::cmp::Reverse((*date, *modified)));
+
+    let mut freed = 0;
+    for (path, version, _, _, size) in archives.into_iter().skip(keep) {
+        if installed.is_some() && version == installed {
+            continue;
+        }
+        fs::remove_file(&path)
+            .map_err(|e| errors::io_error(&format!("Failed to remove {}", path.display()), &e))?;
+        freed += size;
+    }
+    Ok(freed)
+}
+
+/// Make an extracted Ruffle binary runnable
+#[cfg_attr(target_os = "windows", allow(unused_variables))]
+fn prepare_binary(dir: &std::path::Path, filename: &str) -> Result<(), String> {
+    // Make executable on unix
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    {
+        let ruffle_bin = dir.join(filename);
+        if ruffle_bin.exists() {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = fs::metadata(&ruffle_bin)
+                .map_err(|e| format!("Failed to get permissions: {}", e))?
+                .permissions();
+            perms.set_mode(0o755);
+            fs::set_permissions(&ruffle_bin, perms)
+                .map_err(|e| errors::io_error("Failed to set permissions", &e))?;
+        }
+    }
+
+    // Gatekeeper otherwise blocks the extracted binary on first run
+    #[cfg(target_os = "macos")]
+    archive::clear_quarantine(dir)?;
+
+    Ok(())
+}
+
+#[derive(serde::Serialize)]
+pub struct StoredFile {
+    /// Path relative to the domain's directory
+    pub path: String,
+    pub size: u64,
+    /// Milliseconds since the Unix epoch
+    pub modified_ms: Option<u64>,
+}
+
+#[derive(serde::Serialize)]
+pub struct StorageDomain {
+    /// Host the SWFs that saved this data were loaded from
+    pub domain: String,
+    /// Total size of the domain's files in bytes
+    pub size: u64,
+    pub files: Vec<StoredFile>,
+}
+
+#[derive(serde::Serialize)]
+pub struct RuffleStorage {
+    /// Ruffle save directory that was listed
+    pub storage_dir: String,
+    /// Domains with stored data, sorted by name
+    pub domains: Vec<StorageDomain>,
+}
+
+/// Ruffle's stored SharedObjects, grouped by the domain directory they're
+/// stored under
+#[tauri::command]
+pub fn list_ruffle_storage(
+    settings: tauri::State<'_, Mutex<Settings>>,
+) -> Result<RuffleStorage, String> {
+    let storage_dir = config::resolve_ruffle_storage_dir(&config::lock_settings(&settings))?;
+    let mut domains = Vec::new();
+
+    if let Ok(entries) = fs::read_dir(&storage_dir) {
+        for entry in entries.flatten().filter(|e| e.path().is_dir()) {
+            let dir = entry.path();
+            let mut files: Vec<StoredFile> = walkdir::WalkDir::new(&dir)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file())
+                .filter_map(|e| {
+                    let metadata = e.metadata().ok()?;
+                    Some(StoredFile {
+                        path: e
+                            .path()
+                            .strip_prefix(&dir)
+                            .ok()?
+                            .to_string_lossy()
+                            .into_owned(),
+                        size: metadata.len(),
+                        modified_ms: metadata
+                            .modified()
+                            .ok()
+                            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                            .map(|d| d.as_millis() as u64),
+                    })
+                })
+                .collect();
+            files.sort_by(|a, b| a.path.cmp(&b.path));
+            domains.push(StorageDomain {
+                domain: entry.file_name().to_string_lossy().into_owned(),
+                size: files.iter().map(|f| f.size).sum(),
+                files,
+            });
+        }
+    }
+    domains.sort_by(|a, b| a.domain.cmp(&b.domain));
+
+    Ok(RuffleStorage {
+        storage_dir: storage_dir.to_string_lossy().into_owned(),
+        domains,
+    })
+}
+
+/// Delete everything Ruffle stored for `domain`, as listed by
+/// [`list_ruffle_storage`]. Only a direct subdirectory of the Ruffle save
+/// directory can be removed. Returns the number of bytes freed.
+#[tauri::command]
+pub fn clear_ruffle_storage(
+    domain: String,
+    settings: tauri::State<'_, Mutex<Settings>>,
+    running: tauri::State<'_, crate::process::RunningGames>,
+) -> Result<u64, String> {
+    let is_plain_name = !domain.is_empty()
+        && domain != "."
+        && domain != ".."
+        && !domain.contains(['/', '\\'])
+        && std::path::Path::new(&domain).components().count() == 1;
+    if !is_plain_name {
+        return Err(format!("Invalid storage domain '{}'", domain));
+    }
+    if !running.running_ids().is_empty() {
+        return Err("Close running games before clearing Ruffle storage".to_string());
+    }
+
+    let storage_dir = config::resolve_ruffle_storage_dir(&config::lock_settings(&settings))?;
+    let target = storage_dir.join(&domain);
+    if !target.is_dir() {
+        return Err(format!("No Ruffle storage for '{}'", domain));
+    }
+
+    // Resolve links so a symlinked domain directory can't point elsewhere
+    let root = storage_dir
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve Ruffle storage directory: {}", e))?;
+    let resolved = target
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve Ruffle storage for '{}': {}", domain, e))?;
+    if resolved.parent() != Some(root.as_path()) {
+        return Err(format!(
+            "Ruffle storage for '{}' is outside {}",
+            domain,
+            storage_dir.display()
+        ));
+    }
+
+    let size = walkdir::WalkDir::new(&resolved)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.metadata().ok())
+        .filter(|m| m.is_file())
+        .map(|m| m.len())
+        .sum();
+    fs::remove_dir_all(&resolved)
+        .map_err(|e| errors::io_error("Failed to clear Ruffle storage", &e))?;
+    logger::log(&format!("Cleared Ruffle storage for {}", domain));
+    Ok(size)
+}
+
+#[derive(serde::Serialize)]
+pub struct RuffleInstall {
+    /// Release tag, e.g. `nightly-2026-02-09`
+    pub version: String,
+    /// Cached archive of this build
+    pub archive: Option<String>,
+    /// Extracted binary, if the build has been extracted
+    pub binary: Option<String>,
+    /// Launches use this build
+    pub active: bool,
+}
+
+/// Cached Ruffle archives and extracted builds in the Ruffle cache directory,
+/// by release tag
+fn cached_builds(
+    filename: &str,
+) -> Result<std::collections::BTreeMap<String, RuffleInstall>, String> {
+    let mut builds = std::collections::BTreeMap::new();
+    let cache_dir = config::get_ruffle_cache_dir()?;
+    let Ok(entries) = fs::read_dir(&cache_dir) else {
+        return Ok(builds);
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if path.is_dir() {
+            let binary = path.join(filename);
+            if let (Some(version), true) = (parse_ruffle_version(&name), binary.exists()) {
+                builds
+                    .entry(version.tag())
+                    .or_insert_with(|| RuffleInstall {
+                        version: version.tag(),
+                        archive: None,
+                        binary: None,
+                        active: false,
+                    })
+                    .binary = Some(binary.to_string_lossy().into_owned());
+            }
+        } else if let Some(version) = archive_version(&name) {
+            builds
+                .entry(version.tag())
+                .or_insert_with(|| RuffleInstall {
+                    version: version.tag(),
+                    archive: None,
+                    binary: None,
+                    active: false,
+                })
+                .archive = Some(path.to_string_lossy().into_owned());
+        }
+    }
+    Ok(builds)
+}
+
+/// Ruffle builds available locally: the managed install and every build in
+/// the Ruffle cache, newest first
+#[tauri::command]
+pub fn list_ruffle_installs(
+    config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
+) -> Result<Vec<RuffleInstall>, String> {
+    let (_, filename) = fallback_download(&config);
+    let mut builds = cached_builds(&filename)?;
+
+    let managed_version = config::lock_versions(&versions).ruffle.clone();
+    let managed_binary = config::get_ruffle_dir()?.join(&filename);
+    if !managed_version.is_empty() && managed_binary.exists() {
+        builds
+            .entry(managed_version.clone())
+            .or_insert_with(|| RuffleInstall {
+                version: managed_version.clone(),
+                archive: None,
+                binary: None,
+                active: false,
+            })
+            .binary
+            .get_or_insert_with(|| managed_binary.to_string_lossy().into_owned());
+    }
+
+    let active_path = config::get_ruffle_path(&config, &config::lock_settings(&settings))
+        .ok()
+        .map(|p| p.to_string_lossy().into_owned());
+    let mut installs: Vec<RuffleInstall> = builds
+        .into_values()
+        .map(|mut install| {
+            install.active = install.binary.is_some() && install.binary == active_path
+                || install.version == managed_version
+                    && active_path == Some(managed_binary.to_string_lossy().into_owned());
+            install
+        })
+        .collect();
+    installs.sort_by_key(|install| {
+        let date = match parse_ruffle_version(&install.version) {
+            Some(RuffleVersion::Nightly(date)) => Some(date),
+            _ => None,
+        };
+        std::cmp::Reverse((date, install.version.clone()))
+    });
+    Ok(installs)
+}
+
+/// Make the Ruffle build `version_tag` the one launches use. A cached build
+/// is extracted if needed and checked with `--version` before `ruffle_path`
+/// is pointed at it; the managed install's own tag switches back to it.
+#[tauri::command(async)]
+pub fn set_active_ruffle(
+    version_tag: String,
+    config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
+) -> Result<String, String> {
+    let (_, filename) = fallback_download(&config);
+    let managed_version = config::lock_versions(&versions).ruffle.clone();
+    let managed_binary = config::get_ruffle_dir()?.join(&filename);
+
+    let (binary, ruffle_path) = if version_tag == managed_version && managed_binary.exists() {
+        (managed_binary, None)
+    } else {
+        let version = parse_ruffle_version(&version_tag)
+            .ok_or_else(|| format!("Invalid Ruffle version '{}'", version_tag))?;
+        let build = cached_builds(&filename)?
+            .remove(&version.tag())
+            .ok_or_else(|| format!("No cached Ruffle build '{}'", version_tag))?;
+        let binary = match (build.binary, build.archive) {
+            (Some(binary), _) => std::path::PathBuf::from(binary),
+            (None, Some(archive)) => extract_cached_build(&archive, &version.tag(), &filename)?,
+            (None, None) => return Err(format!("No cached Ruffle build '{}'", version_tag)),
+        };
+        let path = binary.to_string_lossy().into_owned();
+        (binary, Some(path))
+    };
+
+    let current = config::lock_settings(&settings).clone();
+    let test = crate::diagnostics::test_player_path(&binary, "ruffle", &current)?;
+    if !test.started {
+        return Err(format!(
+            "Ruffle build '{}' doesn't run: {}",
+            version_tag,
+            test.error.unwrap_or_default()
+        ));
+    }
+
+    let updated = {
+        let mut settings = config::lock_settings(&settings);
+        settings.ruffle_path = ruffle_path.clone();
+        settings.clone()
+    };
+    config::save_settings(&updated)?;
+    config::update_versions(&versions, |versions| {
+        versions.ruffle_active = ruffle_path.is_some().then(|| version_tag.clone());
+    })?;
+
+    Ok(binary.to_string_lossy().into_owned())
+}
+
+/// Extract a cached archive into `<cache>/<tag>`, returning the binary path
+fn extract_cached_build(
+    archive: &str,
+    tag: &str,
+    filename: &str,
+) -> Result<std::path::PathBuf, String> {
+    let archive = std::path::Path::new(archive);
+    let name = archive
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extract = archive::extractor_for(&name)
+        .ok_or_else(|| format!("Unsupported archive format: {}", name))?;
+
+    let dir = config::get_ruffle_cache_dir()?.join(tag);
+    archive::stage_install(&dir, filename, &[], |staging| {
+        extract(archive, staging)?;
+        let binary = staging.join(filename);
+        if binary.exists() {
+            archive::check_binary_platform(&binary)?;
+        }
+        Ok(())
+    })?;
+    prepare_binary(&dir, filename)?;
+    Ok(dir.join(filename))
+}
+
+#[derive(serde::Serialize)]
+pub struct RuffleUpdate {
+    /// Recorded tag of the installed Ruffle, empty if unknown
+    pub installed: String,
+    pub latest: String,
+    pub update_available: bool,
+}
+
+/// Compare the installed Ruffle against the latest release. Versions are
+/// compared chronologically, so an installed build newer than "latest" (e.g.
+/// after a release was pulled) isn't offered as an update. Tags that can't be
+/// compared are treated as an update whenever they differ.
+#[tauri::command]
+pub async fn check_ruffle_update(
+    settings: tauri::State<'_, Mutex<Settings>>,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
+) -> Result<RuffleUpdate, String> {
+    let (_, _, latest) = fetch_latest_nightly(&settings).await?;
+    let installed = config::lock_versions(&versions).ruffle.clone();
+
+    let update_available = match (
+        parse_ruffle_version(&installed),
+        parse_ruffle_version(&latest),
+    ) {
+        (Some(current), Some(newest)) => match newest.partial_cmp(&current) {
+            Some(order) => order == Ordering::Greater,
+            None => installed != latest,
+        },
+        _ => installed != latest,
+    };
+
+    Ok(RuffleUpdate {
+        installed,
+        latest,
+        update_available,
+    })
+}
+
 #[derive(Debug, serde::Deserialize)]
 struct RuffleAsset {
     name: String,
@@ -48,49 +541,138 @@ struct RuffleAsset {
 #[derive(Debug, serde::Deserialize)]
 struct RuffleRelease {
     tag_name: String,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
     assets: Vec<RuffleAsset>,
 }
 
-async fn fetch_latest_nightly() -> Result<(String, String, String), String> {
-    let client = reqwest::Client::builder()
-        .user_agent("PTDLauncher")
-        .build()
-        .map_err(|e| format!("Failed to create client: {}", e))?;
+/// How many releases to look through for one with an asset for this platform
+const MAX_RELEASES_SCANNED: usize = 10;
+
+/// Default for `release_fetch_timeout_secs`
+const DEFAULT_RELEASE_FETCH_TIMEOUT_SECS: u64 = 15;
 
-    let url = "https://api.github.com/repos/ruffle-rs/ruffle/releases";
-    let response = client
-        .get(url)
-        .send()
-        .await
-        .map_err(|e| format!("Failed to fetch releases: {}", e))?;
+/// How often progress is emitted while the release list is being fetched
+const FETCH_HEARTBEAT: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Fetch a GitHub releases list from `url`
+async fn fetch_releases(
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<Vec<serde_json::Value>, String> {
+    let response = client.get(url).send().await.map_err(|e| {
+        if e.is_timeout() {
+            "Timed out waiting for the release list; the server is slow or unreachable".to_string()
+        } else if e.is_connect() {
+            format!("Couldn't reach the release server: {}", e)
+        } else {
+            format!("Failed to fetch releases: {}", e)
+        }
+    })?;
 
     if !response.status().is_success() {
         return Err(format!("GitHub API error: {}", response.status()));
     }
 
-    let releases: Vec<RuffleRelease> = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse releases: {}", e))?;
+    let body: serde_json::Value = response.json().await.map_err(|e| {
+        if e.is_timeout() {
+            "Timed out reading the release list; the server is slow or unreachable".to_string()
+        } else {
+            format!("Failed to parse releases: {}", e)
+        }
+    })?;
+    match body {
+        serde_json::Value::Array(entries) => Ok(entries),
+        body => {
+            let message = body
+                .get("message")
+                .and_then(|m| m.as_str())
+                .unwrap_or("not a list of releases");
+            Err(format!("Unexpected GitHub API response: {}", message))
+        }
+    }
+}
+
+async fn fetch_latest_nightly(
+    settings: &Mutex<Settings>,
+) -> Result<(String, String, String), String> {
+    let timeout = config::lock_settings(settings)
+        .release_fetch_timeout_secs
+        .unwrap_or(DEFAULT_RELEASE_FETCH_TIMEOUT_SECS);
+    let client = download::client_builder(settings)?
+        .user_agent("PTDLauncher")
+        .timeout(std::time::Duration::from_secs(timeout))
+        .build()
+        .map_err(|e| format!("Failed to create client: {}", e))?;
+
+    let (repo, mirrors) = {
+        let settings = config::lock_settings(settings);
+        (
+            settings
+                .ruffle_repo
+                .clone()
+                .unwrap_or_else(|| config::DEFAULT_RUFFLE_REPO.to_string()),
+            settings.ruffle_metadata_mirrors.clone().unwrap_or_default(),
+        )
+    };
 
-    // Find the latest nightly release (usually the first one, but let's be sure it has assets)
-    let release = releases
-        .first()
-        .ok_or_else(|| "No releases found".to_string())?;
+    // Try the GitHub API first, then each mirror in order, for networks that
+    // block api.github.com but not the release downloads
+    let mut urls = vec![format!("https://api.github.com/repos/{}/releases", repo)];
+    urls.extend(mirrors.iter().map(|m| config::mirror_url(m, &repo)));
+    let mut errors = Vec::new();
+    let mut releases = None;
+    for url in &urls {
+        match fetch_releases(&client, url).await {
+            Ok(entries) => {
+                releases = Some(entries);
+                break;
+            }
+            Err(e) => {
+                logger::log(&format!(
+                    "Failed to fetch Ruffle releases from {}: {}",
+                    config::redact_url(url),
+                    e
+                ));
+                errors.push(e);
+            }
+        }
+    }
+    let entries = releases.ok_or_else(|| errors.join("; "))?;
 
-    // Determine target asset name based on OS
+    // Target asset names for this OS, in order of preference. Windows builds
+    // also come as a tar.gz, which is used when a nightly is missing its zip.
     #[cfg(target_os = "windows")]
-    let target_pattern = "windows-x86_64.zip";
+    let target_patterns = ["windows-x86_64.zip", "windows-x86_64.tar.gz"];
     #[cfg(target_os = "macos")]
-    let target_pattern = "macos-universal.tar.gz";
+    let target_patterns = ["macos-universal.tar.gz"];
     #[cfg(target_os = "linux")]
-    let target_pattern = "linux-x86_64.tar.gz";
-
-    let asset = release
-        .assets
-        .iter()
-        .find(|a| a.name.contains(target_pattern) && !a.name.contains("extension"))
-        .ok_or_else(|| format!("No asset found for target: {}", target_pattern))?;
+    let target_patterns = ["linux-x86_64.tar.gz"];
+
+    // Releases come newest first. Skip drafts, entries that don't parse and
+    // releases that don't (yet) have a build for this platform.
+    let (tag_name, asset) = entries
+        .into_iter()
+        .take(MAX_RELEASES_SCANNED)
+        .filter_map(|entry| serde_json::from_value::<RuffleRelease>(entry).ok())
+        .filter(|release| !release.draft)
+        .find_map(|mut release| {
+            let pos = target_patterns.iter().find_map(|pattern| {
+                release
+                    .assets
+                    .iter()
+                    .position(|a| a.name.contains(pattern) && !a.name.contains("extension"))
+            })?;
+            Some((release.tag_name, release.assets.swap_remove(pos)))
+        })
+        .ok_or_else(|| {
+            format!(
+                "No release among the latest {} has an asset for target: {}",
+                MAX_RELEASES_SCANNED,
+                target_patterns.join(" or ")
+            )
+        })?;
 
     let filename = if cfg!(target_os = "windows") {
         "ruffle.exe".to_string()
@@ -98,22 +680,103 @@ async fn fetch_latest_nightly() -> Result<(String, String, String), String> {
         "ruffle".to_string()
     };
 
-    Ok((
-        asset.browser_download_url.clone(),
-        filename,
-        release.tag_name.clone(),
-    ))
+    Ok((asset.browser_download_url, filename, tag_name))
+}
+
+/// Download URL and binary name from the config, used when the latest
+/// release can't be looked up
+fn fallback_download(config: &AppConfig) -> (String, String) {
+    #[cfg(target_os = "windows")]
+    let os = &config.ruffle.windows;
+    #[cfg(target_os = "macos")]
+    let os = &config.ruffle.macos;
+    #[cfg(target_os = "linux")]
+    let os = &config.ruffle.linux;
+
+    (os.url.clone(), os.filename.clone())
+}
+
+/// URL `download_ruffle` would download from
+pub async fn download_url(config: &AppConfig, settings: &Mutex<Settings>) -> String {
+    match fetch_latest_nightly(settings).await {
+        Ok((url, _, _)) => url,
+        Err(_) => fallback_download(config).0,
+    }
+}
+
+/// Update the managed Ruffle install in the background when
+/// `auto_update_ruffle` is on, reporting progress through the usual
+/// `download-progress` events. A missing Ruffle isn't installed, and a custom
+/// `ruffle_path` is left alone. Failures, e.g. being offline, are only logged.
+pub async fn auto_update(window: Window) {
+    let (enabled, custom_path) = {
+        let settings = window.state::<Mutex<Settings>>();
+        let settings = config::lock_settings(&settings);
+        (
+            settings.auto_update_ruffle.unwrap_or(false),
+            settings.ruffle_path.clone(),
+        )
+    };
+    if !enabled || config::existing_custom_path(custom_path.as_deref()).is_some() {
+        return;
+    }
+    let installed = !config::lock_versions(&window.state::<Mutex<GameVersions>>())
+        .ruffle
+        .is_empty();
+    if !installed {
+        return;
+    }
+
+    let update = match check_ruffle_update(window.state(), window.state()).await {
+        Ok(update) => update,
+        Err(e) => {
+            logger::log(&format!("Ruffle auto-update check failed: {}", e));
+            return;
+        }
+    };
+    if !update.update_available {
+        return;
+    }
+
+    logger::log(&format!(
+        "Auto-updating Ruffle from {} to {}",
+        update.installed, update.latest
+    ));
+    let downloaded = download_ruffle(
+        window.clone(),
+        None,
+        window.state(),
+        window.state(),
+        window.state(),
+    )
+    .await;
+    if let Err(e) = downloaded {
+        logger::log(&format!("Ruffle auto-update failed: {}", e));
+    }
 }
 
 #[tauri::command]
 pub async fn download_ruffle(
     window: Window,
+    install_dir: Option<String>,
     config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+    versions: tauri::State<'_, Mutex<GameVersions>>,
 ) -> Result<String, String> {
+    // Held from the release lookup until the install is recorded, so a
+    // second download can't stage over this one
+    let download = window
+        .state::<download::ActiveDownloads>()
+        .begin(&window, "ruffle")?;
+
     // Get download info based on OS
-    let ruffle_dir = config::get_ruffle_dir()?;
+    let custom_dir = config::custom_install_dir(install_dir.as_deref(), "Ruffle");
+    let ruffle_dir = match &custom_dir {
+        Some(dir) => dir.clone(),
+        None => config::get_ruffle_dir()?,
+    };
     fs::create_dir_all(&ruffle_dir)
-        .map_err(|e| format!("Failed to create ruffle directory: {}", e))?;
+        .map_err(|e| errors::io_error("Failed to create ruffle directory", &e))?;
 
     // Try to fetch latest nightly
     let _ = window.emit(
@@ -123,11 +786,60 @@ pub async fn download_ruffle(
             progress: 0,
             downloaded: 0,
             total: 0,
+            phase: DownloadPhase::Connecting,
             status: "Fetching latest nightly...".to_string(),
+            seq: download::next_seq(),
         },
     );
 
-    let (url, filename, version_tag) = match fetch_latest_nightly().await {
+    // Keep the UI updated while the release list loads, which can take a
+    // while on a slow link, and let `cancel_download("ruffle")` stop it
+    let started = std::time::Instant::now();
+    let fetch = fetch_latest_nightly(&settings);
+    tokio::pin!(fetch);
+    let mut heartbeat = tokio::time::interval(FETCH_HEARTBEAT);
+    heartbeat.tick().await;
+    let fetched = loop {
+        tokio::select! {
+            result = &mut fetch => break Some(result),
+            _ = download.token().cancelled() => break None,
+            _ = heartbeat.tick() => {
+                let _ = window.emit(
+                    "download-progress",
+                    DownloadProgress {
+                        item: "ruffle".to_string(),
+                        progress: 0,
+                        downloaded: 0,
+                        total: 0,
+                        phase: DownloadPhase::Connecting,
+                        status: format!(
+                            "Fetching latest nightly... ({}s)",
+                            started.elapsed().as_secs()
+                        ),
+                        seq: download::next_seq(),
+                    },
+                );
+            }
+        }
+    };
+
+    let Some(fetched) = fetched else {
+        let _ = window.emit(
+            "download-progress",
+            DownloadProgress {
+                item: "ruffle".to_string(),
+                progress: 0,
+                downloaded: 0,
+                total: 0,
+                phase: DownloadPhase::Cancelled,
+                status: download::CANCELLED_ERROR.to_string(),
+                seq: download::next_seq(),
+            },
+        );
+        return Err(download::CANCELLED_ERROR.to_string());
+    };
+
+    let (url, filename, version_tag) = match fetched {
         Ok(info) => info,
         Err(e) => {
             // Fallback to config
@@ -138,20 +850,14 @@ pub async fn download_ruffle(
                     progress: 0,
                     downloaded: 0,
                     total: 0,
+                    phase: DownloadPhase::Connecting,
                     status: format!("Failed to fetch latest: {}. Using fallback...", e),
+                    seq: download::next_seq(),
                 },
             );
 
-            #[cfg(target_os = "windows")]
-            let (url, filename) = (&config.ruffle.windows.url, &config.ruffle.windows.filename);
-
-            #[cfg(target_os = "macos")]
-            let (url, filename) = (&config.ruffle.macos.url, &config.ruffle.macos.filename);
-
-            #[cfg(target_os = "linux")]
-            let (url, filename) = (&config.ruffle.linux.url, &config.ruffle.linux.filename);
-
-            (url.clone(), filename.clone(), "fallback".to_string())
+            let (url, filename) = fallback_download(&config);
+            (url, filename, "fallback".to_string())
         }
     };
 
@@ -167,45 +873,96 @@ pub async fn download_ruffle(
             progress: 0,
             downloaded: 0,
             total: 0,
+            phase: DownloadPhase::Connecting,
             status: "Starting download...".to_string(),
+            seq: download::next_seq(),
         },
     );
 
     // Download the file
-    download_file_with_progress(&window, &url, &download_path, "ruffle").await?;
-
-    // Extract based on extension
-    if archive_name.ends_with(".zip") {
-        extract_zip(&download_path, &ruffle_dir)?;
-    } else if archive_name.ends_with(".tar.gz") {
-        extract_tar_gz(&download_path, &ruffle_dir)?;
-    } else {
-        return Err(format!("Unsupported archive format: {}", archive_name));
-    }
+    download_file_with_progress(
+        &download,
+        &url,
+        None,
+        &download_path,
+        config.expected_sizes.get("ruffle").copied(),
+    )
+    .await?;
+
+    // Extract based on extension into a clean staging dir, replacing the old install
+    let Some(extract) = archive::extractor_for(archive_name) else {
+        let _ = fs::remove_file(&download_path);
+        let error = format!("Unsupported archive format: {}", archive_name);
+        download::emit_failed(&window, "ruffle", &error);
+        return Err(error);
+    };
 
-    let _ = fs::remove_file(&download_path);
+    // Only tar.gz extraction reports progress as it goes
+    let is_tar_gz = archive_name.ends_with(".tar.gz");
+    download::emit_extracting(&window, "ruffle", if is_tar_gz { 0 } else { 100 });
 
-    // Make executable on unix
-    #[cfg(any(target_os = "linux", target_os = "macos"))]
-    {
-        let ruffle_bin = ruffle_dir.join(&filename);
-        if ruffle_bin.exists() {
-            use std::os::unix::fs::PermissionsExt;
-            let mut perms = fs::metadata(&ruffle_bin)
-                .map_err(|e| format!("Failed to get permissions: {}", e))?
-                .permissions();
-            perms.set_mode(0o755);
-            fs::set_permissions(&ruffle_bin, perms)
-                .map_err(|e| format!("Failed to set permissions: {}", e))?;
+    let installed = archive::stage_install(&ruffle_dir, &filename, &[], |staging| {
+        if is_tar_gz {
+            archive::extract_tar_gz_with_progress(&download_path, staging, |progress| {
+                download::emit_extracting(&window, "ruffle", progress)
+            })?;
+        } else {
+            extract(&download_path, staging)?;
+        }
+        // Checked before the swap, so a wrong asset leaves the old install
+        // alone. A missing binary is reported by stage_install.
+        let binary = staging.join(&filename);
+        if binary.exists() {
+            archive::check_binary_platform(&binary)?;
+        }
+        Ok(())
+    });
+    let cache = installed.is_ok()
+        && config::lock_settings(&settings)
+            .cache_ruffle_archives
+            .unwrap_or(false);
+    if cache {
+        if let Err(e) = cache_archive(&download_path, archive_name) {
+            logger::log(&format!("Warning: failed to cache Ruffle archive: {}", e));
         }
     }
+    let _ = fs::remove_file(&download_path);
+    installed.inspect_err(|e| download::emit_failed(&window, "ruffle", e))?;
+
+    prepare_binary(&ruffle_dir, &filename)
+        .inspect_err(|e| download::emit_failed(&window, "ruffle", e))?;
 
     // Update version info
-    let mut versions = config::load_versions().unwrap_or_default();
-    versions.ruffle = version_tag;
-    config::save_versions(&versions)?;
+    config::update_versions(&versions, |versions| versions.ruffle = version_tag)?;
 
-    // Emit completion
+    let final_path = ruffle_dir.join(filename);
+    download::wait_for_install(&final_path).await?;
+
+    let hashes = integrity::hash_tree_async(ruffle_dir.clone()).await?;
+    config::update_versions(&versions, |versions| integrity::record(versions, hashes))?;
+
+    let final_path = final_path
+        .to_str()
+        .map(|s| s.to_string())
+        .ok_or_else(|| "Invalid path".to_string())?;
+
+    // Point settings at the custom install so launches find it, or back at
+    // the managed install if a build was pinned with `set_active_ruffle`
+    let pinned = config::lock_versions(&versions).ruffle_active.is_some();
+    if custom_dir.is_some() || pinned {
+        let updated = {
+            let mut settings = config::lock_settings(&settings);
+            settings.ruffle_path = custom_dir.is_some().then(|| final_path.clone());
+            settings.clone()
+        };
+        config::save_settings(&updated)?;
+        config::update_versions(&versions, |versions| versions.ruffle_active = None)?;
+    }
+
+    download::run_post_download_hook(&settings, "ruffle", std::path::Path::new(&final_path));
+
+    // Emit completion only once the install is visible and settings point at
+    // it, so a `check_ruffle_installed` triggered by this event sees it
     let _ = window.emit(
         "download-progress",
         DownloadProgress {
@@ -213,90 +970,11 @@ pub async fn download_ruffle(
             progress: 100,
             downloaded: 0,
             total: 0,
+            phase: DownloadPhase::Complete,
             status: "Download complete".to_string(),
+            seq: download::next_seq(),
         },
     );
 
-    let final_path = ruffle_dir.join(filename);
-
-    final_path
-        .to_str()
-        .map(|s| s.to_string())
-        .ok_or_else(|| "Invalid path".to_string())
-}
-
-async fn download_file_with_progress(
-    window: &Window,
-    url: &str,
-    dest: &PathBuf,
-    item_name: &str,
-) -> Result<(), String> {
-    let client = reqwest::Client::new();
-    let response = client
-        .get(url)
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        return Err(format!("HTTP error: {}", response.status()));
-    }
-
-    let total = response.content_length().unwrap_or(0);
-    let mut downloaded: u64 = 0;
-
-    let mut file = fs::File::create(dest).map_err(|e| format!("Failed to create file: {}", e))?;
-
-    let mut stream = response.bytes_stream();
-    use futures_util::StreamExt;
-
-    while let Some(chunk) = stream.next().await {
-        let chunk = chunk.map_err(|e| format!("Download error: {}", e))?;
-        file.write_all(&chunk)
-            .map_err(|e| format!("Write error: {}", e))?;
-
-        downloaded += chunk.len() as u64;
-        let progress = if total > 0 {
-            ((downloaded as f64 / total as f64) * 100.0) as u32
-        } else {
-            0
-        };
-
-        let _ = window.emit(
-            "download-progress",
-            DownloadProgress {
-                item: item_name.to_string(),
-                progress,
-                downloaded,
-                total,
-                status: "Downloading...".to_string(),
-            },
-        );
-    }
-
-    Ok(())
-}
-
-fn extract_zip(archive: &PathBuf, dest: &PathBuf) -> Result<(), String> {
-    let file = fs::File::open(archive).map_err(|e| format!("Failed to open archive: {}", e))?;
-    let mut archive =
-        zip::ZipArchive::new(file).map_err(|e| format!("Failed to read zip archive: {}", e))?;
-
-    archive
-        .extract(dest)
-        .map_err(|e| format!("Failed to extract archive: {}", e))?;
-    Ok(())
-}
-
-fn extract_tar_gz(archive: &PathBuf, dest: &PathBuf) -> Result<(), String> {
-    use flate2::read::GzDecoder;
-    use tar::Archive;
-
-    let file = fs::File::open(archive).map_err(|e| format!("Failed to open archive: {}", e))?;
-    let decoder = GzDecoder::new(file);
-    let mut archive = Archive::new(decoder);
-    archive
-        .unpack(dest)
-        .map_err(|e| format!("Failed to extract archive: {}", e))?;
-    Ok(())
+    Ok(final_path)
 }
@@ -0,0 +1,110 @@
+//! Optional certificate pinning for the launcher's HTTP clients.
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{DigitallySignedStruct, SignatureScheme};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+/// Normalize a pin to lowercase hex without separators, so both
+/// `AB:CD:...` and `abcd...` forms are accepted
+pub fn normalize_pin(pin: &str) -> String {
+    pin.chars()
+        .filter(|c| *c != ':' && !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Whether a pin is a SHA-256 hash in hex
+pub fn is_valid_pin(pin: &str) -> bool {
+    let pin = normalize_pin(pin);
+    pin.len() == 64 && pin.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Build a TLS config that does normal platform verification and additionally
+/// requires the SHA-256 of the leaf certificate's SubjectPublicKeyInfo to be
+/// one of `pins`
+pub fn pinned_tls_config(pins: &[String]) -> Result<rustls::ClientConfig, String> {
+    let provider = CryptoProvider::get_default()
+        .cloned()
+        .unwrap_or_else(|| Arc::new(rustls::crypto::aws_lc_rs::default_provider()));
+
+    let inner = rustls_platform_verifier::Verifier::new(provider.clone())
+        .map_err(|e| format!("Failed to load platform certificate verifier: {}", e))?;
+    let verifier = PinnedVerifier {
+        inner,
+        pins: pins.iter().map(|p| normalize_pin(p)).collect(),
+    };
+
+    let config = rustls::ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("Failed to configure TLS: {}", e))?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth();
+    Ok(config)
+}
+
+#[derive(Debug)]
+struct PinnedVerifier {
+    inner: rustls_platform_verifier::Verifier,
+    pins: Vec<String>,
+}
+
+impl ServerCertVerifier for PinnedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let verified = self.inner.verify_server_cert(
+            end_entity,
+            intermediates,
+            server_name,
+            ocsp_response,
+            now,
+        )?;
+
+        let cert = webpki::EndEntityCert::try_from(end_entity)
+            .map_err(|e| rustls::Error::General(format!("Failed to parse certificate: {}", e)))?;
+        let hash: String = Sha256::digest(cert.subject_public_key_info().as_ref())
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+
+        if self.pins.contains(&hash) {
+            Ok(verified)
+        } else {
+            Err(rustls::Error::General(format!(
+                "Server certificate key {} is not in the pinned set",
+                hash
+            )))
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls12_signature(message, cert, dss)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        self.inner.verify_tls13_signature(message, cert, dss)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.inner.supported_verify_schemes()
+    }
+}
@@ -0,0 +1,251 @@
+//! Bulk operations over all components: updating everything in one go and
+//! estimating the download size of an install.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{Emitter, Manager, Window};
+
+use crate::config::{self, AppConfig, Settings};
+use crate::queue::{FLASH_ITEM, RUFFLE_ITEM};
+use crate::{download, flash, game, ruffle};
+
+/// Overall progress of [`update_all`], emitted as `update-all-progress` before
+/// each component is checked. Downloads still emit their own
+/// `download-progress` events.
+#[derive(Clone, serde::Serialize)]
+pub struct UpdateAllProgress {
+    /// "flash_player", "ruffle" or a game id
+    pub item: String,
+    /// 1-based position of `item`
+    pub step: usize,
+    pub total: usize,
+}
+
+#[derive(Default, serde::Serialize)]
+pub struct UpdateSummary {
+    /// Components that were downloaded
+    pub updated: Vec<String>,
+    /// Components that were already up to date
+    pub current: Vec<String>,
+    /// Components whose check or download failed, with the error
+    pub failed: BTreeMap<String, String>,
+}
+
+/// Check Ruffle and every installed game for updates and download the ones
+/// that are out of date. Flash Player no longer gets releases, so it is only
+/// downloaded if missing, and only when it is the active player or
+/// `game_flash_paths` is in use. Ruffle is skipped when a custom Ruffle path
+/// is in use, since the launcher doesn't manage that install.
+#[tauri::command]
+pub async fn update_all(window: Window) -> Result<UpdateSummary, String> {
+    let (flash_missing, ruffle_managed, game_ids) = {
+        let app_config = window.state::<AppConfig>();
+        let settings = config::lock_settings(&window.state()).clone();
+        let use_ruffle = settings.use_ruffle.unwrap_or(false);
+        let uses_flash = !use_ruffle
+            || settings
+                .game_flash_paths
+                .as_ref()
+                .is_some_and(|paths| !paths.is_empty());
+        let flash_missing = uses_flash
+            && !config::get_flash_player_path(&app_config, &settings).is_ok_and(|p| p.exists());
+        let ruffle_managed = config::existing_custom_path(settings.ruffle_path.as_deref())
+            .is_none()
+            && config::get_ruffle_path(&app_config, &settings).is_ok_and(|p| p.exists());
+        let game_ids: Vec<String> = app_config
+            .game_ids()
+            .into_iter()
+            .filter(|id| game::find_game_path(id).ok().flatten().is_some())
+            .cloned()
+            .collect();
+        (flash_missing, ruffle_managed, game_ids)
+    };
+
+    let mut items = Vec::new();
+    if flash_missing {
+        items.push(FLASH_ITEM.to_string());
+    }
+    if ruffle_managed {
+        items.push(RUFFLE_ITEM.to_string());
+    }
+    items.extend(game_ids);
+
+    let mut summary = UpdateSummary::default();
+    let total = items.len();
+    for (i, item) in items.into_iter().enumerate() {
+        let _ = window.emit(
+            "update-all-progress",
+            UpdateAllProgress {
+                item: item.clone(),
+                step: i + 1,
+                total,
+            },
+        );
+
+        match update_item(&window, &item).await {
+            Ok(true) => summary.updated.push(item),
+            Ok(false) => summary.current.push(item),
+            Err(e) => {
+                summary.failed.insert(item, e);
+            }
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Update one component if needed, returning whether it was downloaded
+async fn update_item(window: &Window, item: &str) -> Result<bool, String> {
+    match item {
+        FLASH_ITEM => {
+            flash::download_flash(
+                window.clone(),
+                None,
+                window.state(),
+                window.state(),
+                window.state(),
+            )
+            .await?;
+            Ok(true)
+        }
+        RUFFLE_ITEM => {
+            if !ruffle::check_ruffle_update(window.state(), window.state())
+                .await?
+                .update_available
+            {
+                return Ok(false);
+            }
+            ruffle::download_ruffle(
+                window.clone(),
+                None,
+                window.state(),
+                window.state(),
+                window.state(),
+            )
+            .await?;
+            Ok(true)
+        }
+        game_id => {
+            let update = game::check_game_update(
+                game_id.to_string(),
+                window.state(),
+                window.state(),
+                window.state(),
+            )
+            .await?;
+            if !update.update_available {
+                return Ok(false);
+            }
+            game::download_game(
+                window.clone(),
+                game_id.to_string(),
+                window.state(),
+                window.state(),
+                window.state(),
+            )
+            .await?;
+            Ok(true)
+        }
+    }
+}
+
+/// How long each size lookup in [`estimate_install_size`] may take
+const SIZE_CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(serde::Serialize)]
+pub struct SizeEstimate {
+    /// "flash_player", "ruffle" or a game id
+    pub item: String,
+    /// Advertised size in bytes, `None` if the server didn't say
+    pub bytes: Option<u64>,
+}
+
+#[derive(serde::Serialize)]
+pub struct InstallSizeEstimate {
+    pub items: Vec<SizeEstimate>,
+    /// Sum of the known sizes
+    pub total: u64,
+    /// Whether any item's size is unknown, making `total` a lower bound
+    pub incomplete: bool,
+}
+
+/// Estimate how much installing `game_ids` will download, by asking the server
+/// for each file's size. The default player is included if it isn't
+/// installed yet.
+#[tauri::command]
+pub async fn estimate_install_size(
+    game_ids: Vec<String>,
+    app_config: tauri::State<'_, AppConfig>,
+    settings: tauri::State<'_, Mutex<Settings>>,
+) -> Result<InstallSizeEstimate, String> {
+    let current = config::lock_settings(&settings).clone();
+
+    // (item, url, extra headers)
+    let mut requests: Vec<(String, String, HashMap<String, String>)> = Vec::new();
+    if current.use_ruffle.unwrap_or(false) {
+        if !config::get_ruffle_path(&app_config, &current).is_ok_and(|p| p.exists()) {
+            let url = ruffle::download_url(&app_config, &settings).await;
+            requests.push((RUFFLE_ITEM.to_string(), url, HashMap::new()));
+        }
+    } else if !config::get_flash_player_path(&app_config, &current).is_ok_and(|p| p.exists()) {
+        let url = flash::download_url(&app_config, &current).to_string();
+        requests.push((FLASH_ITEM.to_string(), url, HashMap::new()));
+    }
+    for game_id in game_ids {
+        let url = app_config
+            .resolve_game_url(&game_id, &current)
+            .ok_or_else(|| format!("Game '{}' not found in configuration", game_id))?;
+        let headers = app_config
+            .game_headers
+            .get(&game_id)
+            .cloned()
+            .unwrap_or_default();
+        requests.push((game_id, url, headers));
+    }
+
+    let client = download::client_builder(&settings)?
+        .timeout(SIZE_CHECK_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+    let items: Vec<SizeEstimate> =
+        futures_util::future::join_all(requests.into_iter().map(|(item, url, headers)| {
+            let client = &client;
+            async move {
+                let bytes = remote_size(client, &url, &headers).await;
+                SizeEstimate { item, bytes }
+            }
+        }))
+        .await;
+
+    Ok(InstallSizeEstimate {
+        total: items.iter().filter_map(|i| i.bytes).sum(),
+        incomplete: items.iter().any(|i| i.bytes.is_none()),
+        items,
+    })
+}
+
+/// Content-Length from a HEAD request, or `None` if it fails or isn't sent
+async fn remote_size(
+    client: &reqwest::Client,
+    url: &str,
+    headers: &HashMap<String, String>,
+) -> Option<u64> {
+    let mut request = client.head(url);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    let response = request.send().await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    // HEAD responses have no body, so read the header rather than content_length()
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_LENGTH)?
+        .to_str()
+        .ok()?
+        .parse()
+        .ok()
+}
@@ -3,7 +3,14 @@
  */
 
 import { useState, useEffect } from 'react';
-import { GameInfo, isGameDownloaded, downloadGame, launchGame, DownloadProgress } from '../lib/api';
+import {
+  GameInfo,
+  isGameDownloaded,
+  downloadGame,
+  launchGame,
+  DownloadProgress,
+  createProgressFilter,
+} from '../lib/api';
 import { listen } from '@tauri-apps/api/event';
 import './GameCard.css';
 
@@ -33,11 +40,12 @@ export function GameCard({ game, onStatusChange }: GameCardProps) {
 
   // Listen for download progress events
   useEffect(() => {
+    const isCurrent = createProgressFilter();
     const unlisten = listen<DownloadProgress>('download-progress', (event) => {
-      if (event.payload.item === game.id) {
+      if (event.payload.item === game.id && isCurrent(event.payload)) {
         setProgress(event.payload.progress);
 
-        if (event.payload.progress >= 100) {
+        if (event.payload.phase === 'complete') {
           setIsDownloaded(true);
           setProgress(null);
           setIsLoading(false);
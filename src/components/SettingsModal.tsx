@@ -7,11 +7,11 @@ import {
   Settings,
   getSettings,
   saveSettings,
-  checkFlashInstalled,
+  getInstalledPlayers,
   downloadFlash,
-  checkRuffleInstalled,
   downloadRuffle,
   DownloadProgress,
+  createProgressFilter,
 } from '../lib/api';
 import { listen } from '@tauri-apps/api/event';
 import './SettingsModal.css';
@@ -32,19 +32,19 @@ export function SettingsModal({ isOpen, onClose, onStatusChange }: SettingsModal
   useEffect(() => {
     if (isOpen) {
       loadSettings();
-
-      checkFlash();
-      checkRuffle();
+      checkPlayers();
     }
   }, [isOpen]);
 
   // Listen for download progress
   useEffect(() => {
+    const isCurrent = createProgressFilter();
     const unlisten = listen<DownloadProgress>('download-progress', (event) => {
+      if (!isCurrent(event.payload)) return;
       if (event.payload.item === 'flash_player') {
         setDownloadProgress(event.payload.progress);
 
-        if (event.payload.progress >= 100) {
+        if (event.payload.phase === 'complete') {
           setFlashInstalled(true);
           setDownloadProgress(null);
           setIsDownloading(false);
@@ -52,7 +52,7 @@ export function SettingsModal({ isOpen, onClose, onStatusChange }: SettingsModal
       } else if (event.payload.item === 'ruffle') {
         setDownloadProgress(event.payload.progress);
 
-        if (event.payload.progress >= 100) {
+        if (event.payload.phase === 'complete') {
           setRuffleInstalled(true);
           setDownloadProgress(null);
           setIsDownloading(false);
@@ -74,21 +74,13 @@ export function SettingsModal({ isOpen, onClose, onStatusChange }: SettingsModal
     }
   }
 
-  async function checkFlash() {
-    try {
-      const installed = await checkFlashInstalled();
-      setFlashInstalled(installed);
-    } catch (err) {
-      console.error('Failed to check Flash:', err);
-    }
-  }
-
-  async function checkRuffle() {
+  async function checkPlayers() {
     try {
-      const installed = await checkRuffleInstalled();
-      setRuffleInstalled(installed);
+      const players = await getInstalledPlayers();
+      setFlashInstalled(players.flash);
+      setRuffleInstalled(players.ruffle);
     } catch (err) {
-      console.error('Failed to check Ruffle:', err);
+      console.error('Failed to check players:', err);
     }
   }
 
@@ -190,6 +182,11 @@ export function SettingsModal({ isOpen, onClose, onStatusChange }: SettingsModal
                 Ruffle
               </label>
             </div>
+            {flashInstalled === false && ruffleInstalled === false && (
+              <p className="no-player-notice">
+                No player installed. Download Flash Player or Ruffle below to play.
+              </p>
+            )}
           </div>
 
           {!settings.use_ruffle ? (
@@ -5,14 +5,115 @@ export interface Settings {
   use_ruffle?: boolean;
   ruffle_path?: string;
   sound_enabled?: boolean;
+  ruffle_scale?: RuffleScale;
+  ruffle_width?: number;
+  ruffle_height?: number;
+  keep_old_versions?: boolean;
+  macos_flash_symlink?: boolean;
+  bypass_cache?: boolean;
+  /** Id of the most recently launched game */
+  last_played?: GameId;
+  /** Advanced: hex SHA-256 hashes of allowed server certificate keys */
+  pinned_cert_sha256?: string[];
+  /** Ruffle save data directory, relative paths are under the app directory */
+  ruffle_storage_dir?: string;
+  /** Base URL of a PTD mirror that game URLs are rewritten to */
+  backend_base?: string;
+  /** Linux only: run the Windows Flash projector through Wine */
+  use_wine_flash?: boolean;
+  /** Debug: log the status and key response headers of each download */
+  log_response_headers?: boolean;
+  /** Extra environment variables for the player process */
+  launch_env?: Record<string, string>;
+  /** Allow `launch_env` to override variables like PATH */
+  launch_env_override_critical?: boolean;
+  /** Largest download allowed in bytes (default 500 MB); 0 removes the limit */
+  max_download_bytes?: number;
+  /** GitHub `owner/name` to download Ruffle nightlies from (default `ruffle-rs/ruffle`) */
+  ruffle_repo?: string;
+  /**
+   * Program run after each successful download with the item name and path.
+   * Runs with the launcher's permissions, so only use a trusted executable.
+   */
+  post_download_command?: string;
+  /** Download Ruffle updates in the background on startup (off by default) */
+  auto_update_ruffle?: boolean;
+  /**
+   * GitHub releases API mirrors tried when api.github.com is unreachable;
+   * `{repo}` is replaced with the Ruffle repo
+   */
+  ruffle_metadata_mirrors?: string[];
+  /** Seconds to wait for each Ruffle release list request; defaults to 15 */
+  release_fetch_timeout_secs?: number;
+  /** Keep downloaded Ruffle archives for pruneRuffleCache instead of deleting them */
+  cache_ruffle_archives?: boolean;
+  /** Download each game into its own subfolder of the games directory */
+  games_in_subfolders?: boolean;
+  /** RUST_LOG filter for Ruffle, e.g. "debug"; its output then goes to ruffle.log */
+  ruffle_log_level?: string;
+  /** Flash projector to use for specific games instead of the global one */
+  game_flash_paths?: Partial<Record<GameId, string>>;
+  /** Launch once more when the player fails right after starting */
+  auto_retry_launch?: boolean;
 }
 
+export type RuffleScale = 'exact' | 'show_all' | 'no_border' | 'no_scale';
+
+export type DownloadPhase =
+  | 'connecting'
+  | 'downloading'
+  | 'extracting'
+  | 'verifying'
+  | 'complete'
+  | 'failed'
+  | 'cancelled';
+
 export interface DownloadProgress {
   item: string;
   progress: number;
   downloaded: number;
   total: number;
+  phase: DownloadPhase;
   status: string;
+  /** Increases with every event; see `createProgressFilter` */
+  seq: number;
+}
+
+/**
+ * Returns a predicate that rejects `download-progress` events older than one
+ * already seen for the same item, since IPC can deliver them out of order.
+ */
+export function createProgressFilter(): (progress: DownloadProgress) => boolean {
+  const lastSeq = new Map<string, number>();
+  return (progress) => {
+    const last = lastSeq.get(progress.item);
+    if (last !== undefined && progress.seq <= last) return false;
+    lastSeq.set(progress.item, progress.seq);
+    return true;
+  };
+}
+
+export interface InstalledGame {
+  id: GameId;
+  path: string;
+  version: string | null;
+  /** When the installed build was downloaded (RFC 3339) */
+  installed_at: string | null;
+  play_count: number;
+}
+
+/** Snapshot of everything the home screen needs on startup */
+export interface AppState {
+  settings: Settings;
+  active_player: 'flash' | 'ruffle';
+  flash_installed: boolean;
+  ruffle_installed: boolean;
+  flash_version: string;
+  ruffle_version: string;
+  installed_games: InstalledGame[];
+  /** Null until an update check has been run separately */
+  ruffle_update_available: boolean | null;
+  onboarding_complete: boolean;
 }
 
 export type GameId = 'PTD1' | 'PTD1_Hacked' | 'PTD2' | 'PTD2_Hacked' | 'PTD3' | 'PTD3_Hacked';
@@ -45,6 +146,87 @@ export const GAMES: GameInfo[] = [
   },
 ];
 
+/** A game as described by the launcher's config */
+export interface GameEntry {
+  url: string;
+  display_name: string;
+  description: string;
+  icon_url?: string;
+  /** SHA-256 of the copy bundled in the app resources, if any */
+  bundled_sha256?: string;
+  /** Position in the game list; lower comes first */
+  order?: number;
+  /** Extra Ruffle flags for this game's loader quirks */
+  ruffle_load_behavior?: string[];
+}
+
+export interface GameListing extends GameEntry {
+  id: GameId;
+}
+
+// Download commands
+
+/**
+ * Download "flash_player", "ruffle" or a game as usual while writing a
+ * detailed trace of the request, headers and every chunk to `tracePath`.
+ * Resolves to the installed path.
+ */
+export async function downloadWithTrace(item: string, tracePath: string): Promise<string> {
+  return invoke<string>('download_with_trace', { item, tracePath });
+}
+
+export interface DataUsage {
+  total_bytes: number;
+  /** Bytes by item ("flash_player", "ruffle" or a game id) */
+  items: Record<string, number>;
+  /** When counting started (seconds since the Unix epoch), null if nothing was downloaded since the last reset */
+  since: number | null;
+}
+
+/** Bytes downloaded since counting started or was last reset, including failed downloads */
+export async function getDataUsage(): Promise<DataUsage> {
+  return invoke<DataUsage>('get_data_usage');
+}
+
+export async function resetDataUsage(): Promise<void> {
+  return invoke<void>('reset_data_usage');
+}
+
+/** Cancel a single in-flight download; resolves false if it wasn't running */
+export async function cancelDownload(item: string): Promise<boolean> {
+  return invoke<boolean>('cancel_download', { item });
+}
+
+/** Cancel every in-flight download, returning the cancelled items */
+export async function cancelAllDownloads(): Promise<string[]> {
+  return invoke<string[]>('cancel_all_downloads');
+}
+
+// Download queue commands
+
+export type QueueStatus = 'pending' | 'active' | 'done' | 'failed';
+
+export interface QueuedDownload {
+  /** 'flash_player', 'ruffle' or a game id */
+  item: string;
+  status: QueueStatus;
+  error?: string | null;
+}
+
+/** Add a download to the queue, returning the updated queue */
+export async function enqueueDownload(item: string): Promise<QueuedDownload[]> {
+  return invoke<QueuedDownload[]>('enqueue_download', { item });
+}
+
+export async function getQueue(): Promise<QueuedDownload[]> {
+  return invoke<QueuedDownload[]>('get_queue');
+}
+
+/** Move the listed pending downloads to the front of the queue, in order */
+export async function reorderQueue(newOrder: string[]): Promise<QueuedDownload[]> {
+  return invoke<QueuedDownload[]>('reorder_queue', { newOrder });
+}
+
 // Flash Player commands
 
 export async function checkFlashInstalled(): Promise<boolean> {
@@ -55,8 +237,9 @@ export async function getFlashPath(): Promise<string> {
   return invoke<string>('get_flash_path');
 }
 
-export async function downloadFlash(): Promise<string> {
-  return invoke<string>('download_flash');
+/** Install Flash Player, optionally into a `Flash` folder inside `installDir` */
+export async function downloadFlash(installDir?: string): Promise<string> {
+  return invoke<string>('download_flash', { installDir });
 }
 
 // Ruffle commands
@@ -69,12 +252,144 @@ export async function getRufflePath(): Promise<string> {
   return invoke<string>('get_ruffle_path');
 }
 
-export async function downloadRuffle(): Promise<string> {
-  return invoke<string>('download_ruffle');
+/** Install Ruffle, optionally into a `Ruffle` folder inside `installDir` */
+export async function downloadRuffle(installDir?: string): Promise<string> {
+  return invoke<string>('download_ruffle', { installDir });
+}
+
+export interface RuffleUpdate {
+  installed: string;
+  latest: string;
+  update_available: boolean;
+}
+
+/** Compare the installed Ruffle release against the latest one */
+export async function checkRuffleUpdate(): Promise<RuffleUpdate> {
+  return invoke<RuffleUpdate>('check_ruffle_update');
+}
+
+/**
+ * Delete cached Ruffle archives except the newest `keep` and the installed
+ * version's, resolving to the bytes freed
+ */
+export async function pruneRuffleCache(keep: number): Promise<number> {
+  return invoke<number>('prune_ruffle_cache', { keep });
+}
+
+export interface StoredFile {
+  /** Path relative to the domain's directory */
+  path: string;
+  size: number;
+  /** Milliseconds since the Unix epoch */
+  modified_ms: number | null;
+}
+
+export interface StorageDomain {
+  /** Host the SWFs that saved this data were loaded from */
+  domain: string;
+  /** Total size of the domain's files in bytes */
+  size: number;
+  files: StoredFile[];
+}
+
+export interface RuffleStorage {
+  /** Ruffle save directory that was listed */
+  storage_dir: string;
+  /** Domains with stored data, sorted by name */
+  domains: StorageDomain[];
+}
+
+/** Ruffle's stored save data, grouped by domain */
+export async function listRuffleStorage(): Promise<RuffleStorage> {
+  return invoke<RuffleStorage>('list_ruffle_storage');
+}
+
+/** Delete everything Ruffle stored for `domain`, resolving to the bytes freed */
+export async function clearRuffleStorage(domain: string): Promise<number> {
+  return invoke<number>('clear_ruffle_storage', { domain });
+}
+
+export interface RuffleInstall {
+  /** Release tag, e.g. "nightly-2026-02-09" */
+  version: string;
+  /** Cached archive of this build */
+  archive: string | null;
+  /** Extracted binary, if the build has been extracted */
+  binary: string | null;
+  /** Launches use this build */
+  active: boolean;
+}
+
+/** The managed Ruffle install and every cached build, newest first */
+export async function listRuffleInstalls(): Promise<RuffleInstall[]> {
+  return invoke<RuffleInstall[]>('list_ruffle_installs');
+}
+
+/**
+ * Switch launches to the Ruffle build `versionTag`, extracting and test-running
+ * a cached build first. Resolves to the binary path.
+ */
+export async function setActiveRuffle(versionTag: string): Promise<string> {
+  return invoke<string>('set_active_ruffle', { versionTag });
 }
 
 // Game commands
 
+export async function getGameMetadata(gameId: GameId): Promise<GameEntry> {
+  return invoke<GameEntry>('get_game_metadata', { gameId });
+}
+
+/** Every game in the catalog in a stable display order */
+export async function listGamesOrdered(): Promise<GameListing[]> {
+  return invoke<GameListing[]>('list_games_ordered');
+}
+
+export interface SaveInfo {
+  /** Ruffle save directory that was searched */
+  storage_dir: string;
+  exists: boolean;
+  /** The most recently written save file of the game */
+  path: string | null;
+  size: number;
+  /** Milliseconds since the Unix epoch */
+  modified_ms: number | null;
+  file_count: number;
+}
+
+/** Locate a game's Ruffle save data and report when it was last written */
+export async function getSaveInfo(gameId: GameId): Promise<SaveInfo> {
+  return invoke<SaveInfo>('get_save_info', { gameId });
+}
+
+/**
+ * Copy the game's newest save file, unchanged, to `destPath` (a file or a
+ * directory) for external save editors. Resolves to the written path.
+ */
+export async function exportSave(
+  gameId: GameId,
+  destPath: string,
+  player?: Player
+): Promise<string> {
+  return invoke<string>('export_save', { gameId, destPath, player });
+}
+
+/**
+ * Replace the game's save of the same name with a .sol file, backing up the
+ * current save first. Resolves to the backup path.
+ */
+export async function importSave(
+  gameId: GameId,
+  sourcePath: string,
+  player?: Player
+): Promise<string> {
+  return invoke<string>('import_save', { gameId, sourcePath, player });
+}
+
+/** Local path of the game's cached icon, or null if it has none */
+export async function getGameIcon(gameId: GameId): Promise<string | null> {
+  return invoke<string | null>('get_game_icon', { gameId });
+}
+
 export async function isGameDownloaded(gameId: GameId): Promise<boolean> {
   return invoke<boolean>('is_game_downloaded', { gameId });
 }
@@ -87,8 +402,194 @@ export async function downloadGame(gameId: GameId): Promise<string> {
   return invoke<string>('download_game', { gameId });
 }
 
-export async function launchGame(gameId: GameId): Promise<void> {
-  return invoke<void>('launch_game', { gameId });
+export interface GameUpdate {
+  installed: boolean;
+  update_available: boolean;
+}
+
+/** Ask the server whether a newer build of an installed game exists */
+export async function checkGameUpdate(gameId: GameId): Promise<GameUpdate> {
+  return invoke<GameUpdate>('check_game_update', { gameId });
+}
+
+/** How `compareGameRemote` compared the local and remote game files */
+export type CompareMethod = 'sha256' | 'etag' | 'last_modified' | 'size' | 'none';
+
+export interface RemoteComparison {
+  installed: boolean;
+  /** Whether the installed build is the one on the server; null when nothing could be compared */
+  matches: boolean | null;
+  method: CompareMethod;
+  /** SHA-256 of the installed file, when a remote one was found */
+  local_sha256: string | null;
+  remote_sha256: string | null;
+}
+
+/**
+ * Check the installed build against the server without downloading it, by a
+ * published SHA-256 when available, otherwise by ETag, Last-Modified or size
+ */
+export async function compareGameRemote(gameId: GameId): Promise<RemoteComparison> {
+  return invoke<RemoteComparison>('compare_game_remote', { gameId });
+}
+
+/** Save a game's SWF to `destPath` without installing it; progress events use
+ * the item `${gameId}_export` */
+export async function downloadGameTo(gameId: GameId, destPath: string): Promise<string> {
+  return invoke<string>('download_game_to', { gameId, destPath });
+}
+
+export interface PrunableFile {
+  path: string;
+  size: number;
+}
+
+/** List (and unless dryRun, delete) leftover files in the games directory */
+export async function pruneGamesDir(dryRun: boolean): Promise<PrunableFile[]> {
+  return invoke<PrunableFile[]>('prune_games_dir', { dryRun });
+}
+
+export interface ImportedGame {
+  path: string;
+  game_id: GameId;
+}
+
+export interface ImportProblem {
+  path: string;
+  reason: string;
+}
+
+export interface ImportReport {
+  imported: ImportedGame[];
+  /** Unrecognized file names and games that are already installed */
+  skipped: ImportProblem[];
+  /** Invalid SWFs and files that couldn't be copied */
+  failed: ImportProblem[];
+}
+
+/** Copy the SWFs of known games from a folder (e.g. the old launcher's) into the games directory */
+export async function importGamesFolder(sourceDir: string): Promise<ImportReport> {
+  return invoke<ImportReport>('import_games_folder', { sourceDir });
+}
+
+/** Revert a game to its previous build, returning the version now active */
+export async function rollbackGame(gameId: GameId): Promise<string> {
+  return invoke<string>('rollback_game', { gameId });
+}
+
+export type Player = 'flash' | 'ruffle';
+
+/**
+ * Launch a game, optionally with a specific player instead of the saved default.
+ * Rejects if the game is already running unless `force` is set, which restarts it.
+ */
+export async function launchGame(gameId: GameId, player?: Player, force?: boolean): Promise<void> {
+  return invoke<void>('launch_game', { gameId, player, force });
+}
+
+/** Launch the last played game with the default player, resolving to its id */
+export async function relaunchLast(): Promise<GameId> {
+  return invoke<GameId>('relaunch_last');
+}
+
+/** The launch check that failed in `safeLaunch`, thrown as the rejection value */
+export interface LaunchError {
+  kind:
+    | 'game_not_downloaded'
+    | 'invalid_game_file'
+    | 'player_not_installed'
+    | 'invalid_player_path'
+    | 'already_running'
+    | 'launch_failed';
+  message: string;
+}
+
+/**
+ * Launch a game only after checking the game file, the selected player and
+ * that the game isn't already running. Rejects with a `LaunchError` naming
+ * the first check that failed.
+ */
+export async function safeLaunch(gameId: GameId, player?: Player): Promise<void> {
+  return invoke<void>('safe_launch', { gameId, player });
+}
+
+export interface GameReadiness {
+  /** A valid game file is downloaded */
+  downloaded: boolean;
+  /** Player the game would launch with */
+  player: Player;
+  player_installed: boolean;
+  ready_to_play: boolean;
+  /** What to download first, as download queue items: the game id, "flash_player" or "ruffle" */
+  missing: string[];
+}
+
+/** Whether the game can be launched, and if not what needs downloading first */
+export async function getGameReadiness(gameId: GameId, player?: Player): Promise<GameReadiness> {
+  return invoke<GameReadiness>('get_game_readiness', { gameId, player });
+}
+
+export interface LaunchDebugResult {
+  /** The player exited within a few seconds of launching */
+  exited: boolean;
+  exit_code: number | null;
+  output: string;
+}
+
+/** Launch a game while capturing the player's output to diagnose startup failures */
+export async function launchGameDebug(
+  gameId: GameId,
+  player?: Player,
+  force?: boolean
+): Promise<LaunchDebugResult> {
+  return invoke<LaunchDebugResult>('launch_game_debug', { gameId, player, force });
+}
+
+/**
+ * Payload of the `game-crashed` event, emitted when Ruffle exits with a
+ * failure within a few seconds of launching
+ */
+export interface GameCrash {
+  game_id: GameId;
+  exit_code: number | null;
+  /** Signal that killed the player (Unix only) */
+  signal: number | null;
+  /** stderr captured from the player */
+  stderr: string;
+  /** Crashes recorded for this game, including this one */
+  crash_count: number;
+}
+
+/** Payload of the `bundle-progress` event emitted while a playable bundle is written */
+export interface BundleProgress {
+  game_id: GameId;
+  /** Bytes of the game and player written so far */
+  written: number;
+  total: number;
+}
+
+/**
+ * Zip a downloaded game with the current Ruffle binary, a manifest and a play
+ * script for offline use on the same platform. Resolves to the zip path.
+ */
+export async function exportPlayableBundle(gameId: GameId, destPath: string): Promise<string> {
+  return invoke<string>('export_playable_bundle', { gameId, destPath });
+}
+
+/** Ids of the games currently running */
+export async function getRunningGames(): Promise<GameId[]> {
+  return invoke<GameId[]>('get_running_games');
+}
+
+/** Measure download throughput from the game CDN in Mbps */
+export async function testDownloadSpeed(): Promise<number> {
+  return invoke<number>('test_download_speed');
+}
+
+/** Switch game downloads to a PTD mirror after checking it is reachable, or
+ * back to the configured hosts when `base` is omitted */
+export async function setBackend(base?: string): Promise<void> {
+  return invoke('set_backend', { base });
 }
 
 // Settings commands
@@ -97,6 +598,288 @@ export async function getSettings(): Promise<Settings> {
   return invoke<Settings>('get_settings');
 }
 
+/** A settings field that failed validation */
+export interface ValidationError {
+  /** Name of the field in settings.json */
+  field: keyof Settings;
+  message: string;
+}
+
+/** Every validation problem in `settings`, by field, without saving them */
+export async function checkSettings(settings: Settings): Promise<ValidationError[]> {
+  return invoke<ValidationError[]>('check_settings', { newSettings: settings });
+}
+
+/** Save settings; a changed custom player path is checked first and rejected if it doesn't work */
 export async function saveSettings(settings: Settings): Promise<void> {
   return invoke<void>('save_settings', { newSettings: settings });
 }
+
+export interface ConfigSummary {
+  game_count: number;
+  games: Record<string, string>;
+}
+
+/** Check a config.json blob without applying it; rejects with a list of errors */
+export async function validateConfig(json: string): Promise<ConfigSummary> {
+  return invoke<ConfigSummary>('validate_config', { json });
+}
+
+/** Path of the loaded config.json, or null when the built-in default is in use */
+export async function getConfigPath(): Promise<string | null> {
+  return invoke<string | null>('get_config_path');
+}
+
+/** Open config.json for editing, creating it from the default if needed; returns its path */
+export async function openConfigFile(): Promise<string> {
+  return invoke<string>('open_config_file');
+}
+
+/**
+ * Put back the config.json from before its last write, keeping the current one
+ * as a backup. Applies on the next start; returns the config path.
+ */
+export async function restorePreviousConfig(): Promise<string> {
+  return invoke<string>('restore_previous_config');
+}
+
+/** The player used when a launch doesn't pick one */
+export async function getDefaultPlayer(): Promise<Player> {
+  return invoke<Player>('get_default_player');
+}
+
+/** Set only the default player; emits `settings-changed` with the new settings */
+export async function setDefaultPlayer(player: Player): Promise<void> {
+  return invoke<void>('set_default_player', { player });
+}
+
+export async function getAppState(): Promise<AppState> {
+  return invoke<AppState>('get_app_state');
+}
+
+export interface InstalledPlayers {
+  flash: boolean;
+  ruffle: boolean;
+}
+
+/** Check whether Flash Player and Ruffle are installed in one call */
+export async function getInstalledPlayers(): Promise<InstalledPlayers> {
+  return invoke<InstalledPlayers>('get_installed_players');
+}
+
+/** Re-create missing app data directories, returning the ones that were created */
+export async function reinitialize(): Promise<string[]> {
+  return invoke<string[]>('reinitialize');
+}
+
+// Update commands
+
+/** Payload of `update-all-progress`, emitted before each component is checked */
+export interface UpdateAllProgress {
+  item: string;
+  step: number;
+  total: number;
+}
+
+export interface UpdateSummary {
+  updated: string[];
+  current: string[];
+  failed: Record<string, string>;
+}
+
+/** Update Ruffle and all installed games, and install Flash Player if it is used and missing */
+export async function updateAll(): Promise<UpdateSummary> {
+  return invoke<UpdateSummary>('update_all');
+}
+
+export interface SizeEstimate {
+  item: string;
+  /** Size in bytes, null if the server didn't advertise it */
+  bytes: number | null;
+}
+
+export interface InstallSizeEstimate {
+  items: SizeEstimate[];
+  total: number;
+  /** Some sizes are unknown, so `total` is a lower bound */
+  incomplete: boolean;
+}
+
+/** Estimate the download size of installing these games plus the default player if missing */
+export async function estimateInstallSize(gameIds: GameId[]): Promise<InstallSizeEstimate> {
+  return invoke<InstallSizeEstimate>('estimate_install_size', { gameIds });
+}
+
+// Diagnostics commands
+
+export interface DirectoryCheck {
+  path: string;
+  exists: boolean;
+  writable: boolean;
+}
+
+export interface PlayerCheck {
+  path: string | null;
+  /** The path comes from settings rather than the managed install */
+  custom: boolean;
+  installed: boolean;
+  version: string;
+}
+
+/** Linux packaging sandbox the launcher runs in */
+export type Sandbox = 'none' | 'flatpak' | 'snap' | 'app_image';
+
+export interface Diagnostics {
+  launcher_version: string;
+  os: string;
+  arch: string;
+  sandbox: Sandbox;
+  active_player: 'flash' | 'ruffle';
+  directories: Record<string, DirectoryCheck>;
+  flash: PlayerCheck;
+  ruffle: PlayerCheck;
+  installed_games: Record<string, string>;
+  player_paths: PlayerPathCheck[];
+  /** Download hosts with recent failures, including ones being skipped */
+  host_circuits: HostCircuit[];
+  log_path: string | null;
+}
+
+export interface HostCircuit {
+  host: string;
+  consecutive_failures: number;
+  /** The host is being skipped after repeated failures */
+  open: boolean;
+  /** Seconds until the host is tried again, while open */
+  retry_in_secs: number | null;
+}
+
+export interface PlayerPathCheck {
+  player: Player;
+  path: string;
+  /** Why the path doesn't look like the expected player, null if it does */
+  problem: string | null;
+  /** Whether the problem stops a launch; a file name check is only a warning */
+  blocking: boolean;
+}
+
+export async function runDiagnostics(): Promise<Diagnostics> {
+  return invoke<Diagnostics>('run_diagnostics');
+}
+
+export type InstallSource = 'custom' | 'managed';
+
+export interface InstallEntry {
+  source: InstallSource;
+  path: string | null;
+  exists: boolean;
+  version: string;
+}
+
+export interface InstallReport {
+  flash: InstallEntry;
+  ruffle: InstallEntry;
+  games: Record<string, InstallEntry>;
+}
+
+export interface VerifyReport {
+  checked: number;
+  rehashed: number;
+  missing: string[];
+  modified: string[];
+  unreadable: string[];
+}
+
+/** Check installed files against hashes recorded at install time; `deep`
+ * re-hashes files even when their size and timestamp are unchanged */
+export async function verifyInstallation(deep?: boolean): Promise<VerifyReport> {
+  return invoke<VerifyReport>('verify_installation', { deep });
+}
+
+/** Check that custom player paths point at the right players */
+export async function validatePlayerPaths(): Promise<PlayerPathCheck[]> {
+  return invoke<PlayerPathCheck[]>('validate_player_paths');
+}
+
+export interface PlayerTest {
+  /** The player could be started */
+  started: boolean;
+  /** Version line from `ruffle --version`; null for Flash */
+  version: string | null;
+  /** Why the player couldn't be started */
+  error: string | null;
+}
+
+/** Start the player binary at `path` to check it actually runs */
+export async function testPlayer(path: string, kind: Player): Promise<PlayerTest> {
+  return invoke<PlayerTest>('test_player', { path, kind });
+}
+
+/** Which Linux packaging sandbox (Flatpak, Snap, AppImage) the launcher runs in, if any */
+export async function detectSandbox(): Promise<Sandbox> {
+  return invoke<Sandbox>('detect_sandbox');
+}
+
+export interface HostReachability {
+  host: string;
+  /** URL that was requested */
+  url: string;
+  /** The host answered, with any HTTP status */
+  reachable: boolean;
+  latency_ms: number | null;
+  status: number | null;
+  /** Why the host couldn't be reached */
+  error: string | null;
+}
+
+/** Check which game, player and GitHub hosts can be reached */
+export async function checkConnectivity(): Promise<HostReachability[]> {
+  return invoke<HostReachability[]>('check_connectivity');
+}
+
+export interface LowDiskStatus {
+  /** The launcher's data directory */
+  path: string;
+  /** null when the volume couldn't be checked */
+  free_bytes: number | null;
+  threshold_bytes: number;
+  /** Free space is below the threshold */
+  low: boolean;
+  error: string | null;
+}
+
+/** Whether the drive holding the launcher's data has less than `thresholdMb` MB free */
+export async function checkLowDisk(thresholdMb: number): Promise<LowDiskStatus> {
+  return invoke<LowDiskStatus>('check_low_disk', { thresholdMb });
+}
+
+/** Which player and game files will be used, and whether they come from custom paths */
+export async function getInstallReport(): Promise<InstallReport> {
+  return invoke<InstallReport>('get_install_report');
+}
+
+export interface VersionInfo {
+  launcher_version: string;
+  tauri_version: string;
+  /** Commit the launcher was built from, if known */
+  git_commit: string | null;
+  /** Installed Flash Player version, empty if unknown */
+  flash_version: string;
+  /** Installed Ruffle version, empty if unknown */
+  ruffle_version: string;
+}
+
+/** The launcher build and installed player versions, for bug reports */
+export async function getVersionInfo(): Promise<VersionInfo> {
+  return invoke<VersionInfo>('get_version_info');
+}
+
+/** Contents of ruffle.log, written while `ruffle_log_level` is set */
+export async function getRuffleLog(): Promise<string> {
+  return invoke<string>('get_ruffle_log');
+}
+
+/** Zip logs, redacted settings and diagnostics for a bug report, returning the bundle path */
+export async function createSupportBundle(destPath: string): Promise<string> {
+  return invoke<string>('create_support_bundle', { destPath });
+}